@@ -1,13 +1,37 @@
 use std::fmt;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use actix_web::{get, http::StatusCode, web, HttpResponse, ResponseError};
-use yeax::App;
+use serde::Deserialize;
+use yeax::{App, AppRef, Configurable, Event, Events};
 
-type Hook<T> = Rc<dyn Fn(&str, &str) -> Result<T, AuthError>>;
+type Hook<T> = Arc<dyn Fn(&str, &str) -> Result<T, AuthError> + Send + Sync>;
 
-#[derive(Default, Clone)]
+/// The settings of the auth app, read from the `auth` section
+#[derive(Deserialize)]
+pub struct AuthSettings {
+    #[serde(default = "default_usernames")]
+    usernames: Vec<String>,
+}
+
+fn default_usernames() -> Vec<String> {
+    ["admin", "manager", "user", "modir"]
+        .iter()
+        .map(|username| username.to_string())
+        .collect()
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            usernames: default_usernames(),
+        }
+    }
+}
+
+#[derive(Default)]
 struct AuthConfig {
+    settings: AuthSettings,
     pre_auth: Vec<Hook<()>>,
     post_auth: Vec<Hook<AuthHookResponse>>,
 }
@@ -17,6 +41,13 @@ pub struct AuthApp {
     config: AuthConfig,
 }
 
+/// Published once a user logs in successfully
+pub struct LoggedIn {
+    pub username: String,
+}
+
+impl Event for LoggedIn {}
+
 pub enum AuthHookResponse {
     Respond(HttpResponse),
     Continue,
@@ -25,45 +56,60 @@ pub enum AuthHookResponse {
 impl AuthApp {
     pub fn on_pre_auth<F>(&mut self, f: F)
     where
-        F: Fn(&str, &str) -> Result<(), AuthError> + 'static,
+        F: Fn(&str, &str) -> Result<(), AuthError> + Send + Sync + 'static,
     {
-        self.config.pre_auth.push(Rc::new(f))
+        self.config.pre_auth.push(Arc::new(f))
     }
 
     pub fn on_post_auth<F>(&mut self, f: F)
     where
-        F: Fn(&str, &str) -> Result<AuthHookResponse, AuthError> + 'static,
+        F: Fn(&str, &str) -> Result<AuthHookResponse, AuthError> + Send + Sync + 'static,
     {
-        self.config.post_auth.push(Rc::new(f))
+        self.config.post_auth.push(Arc::new(f))
     }
 }
 
 impl App for AuthApp {
+    fn mount_point(&self) -> &str {
+        "/auth"
+    }
+
     fn configure_web(&self, cfg: &mut web::ServiceConfig) {
-        cfg.service(login).data(self.config.clone());
+        cfg.service(login);
     }
 }
 
-/////////////////////////////////////////////////////////////////////////////////////////////////////
+impl Configurable for AuthApp {
+    type Config = AuthSettings;
 
-const USERNAMES: &[&str] = &["admin", "manager", "user", "modir"];
+    const NAMESPACE: &'static str = "auth";
+
+    fn configure(&mut self, settings: AuthSettings) {
+        self.config.settings = settings;
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
 
 #[get("/{username}/{password}")]
 async fn login(
     web::Path((username, password)): web::Path<(String, String)>,
-    cfg: web::Data<AuthConfig>,
+    auth: AppRef<AuthApp>,
+    events: Events,
 ) -> Result<HttpResponse, AuthError> {
+    let cfg = &auth.config;
     for hook in cfg.pre_auth.iter() {
         hook(username.as_str(), password.as_str())?;
     }
 
-    if username == password && USERNAMES.contains(&username.as_str()) {
+    if username == password && cfg.settings.usernames.contains(&username) {
         for hook in cfg.post_auth.iter() {
             match hook(username.as_str(), password.as_str())? {
                 AuthHookResponse::Continue => {}
                 AuthHookResponse::Respond(response) => return Ok(response),
             }
         }
+        events.publish(LoggedIn { username }).await;
         Ok(HttpResponse::Ok().body(String::from("Success")))
     } else {
         Err(AuthError::InvalidCredentials)
@@ -1,12 +1,20 @@
-use yeax::{App, Registry};
+use yeax::{App, AppId, Registry};
 
-use crate::auth::{AuthApp, AuthError};
+use crate::auth::{AuthApp, AuthError, LoggedIn};
 
 #[derive(Default)]
 pub struct BanningApp {}
 
 impl App for BanningApp {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<AuthApp>()]
+    }
+
     fn init(&mut self, r: &mut Registry) {
+        r.subscribe(0, |event: &LoggedIn| {
+            log::info!("`{}` logged in", event.username);
+        });
+
         r.register_di(|auth_app: &mut AuthApp| {
             auth_app.on_pre_auth(|username, _| {
                 if username == "admin" {
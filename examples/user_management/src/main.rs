@@ -1,5 +1,7 @@
+use std::time::Duration;
+
 use actix_web::{App, HttpServer};
-use yeax::{ActixReactorExt, Reactor};
+use yeax::{ActixMiddlewareExt, ActixReactorExt, Reactor, Settings};
 
 mod auth;
 mod banning;
@@ -11,15 +13,28 @@ use secondfactor::SecondFactorApp;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| {
-        let r = Reactor::default()
-            .add(AuthApp::default())
-            .add(BanningApp::default())
-            .add(SecondFactorApp::default())
-            .build();
-        App::new().configure_app(&r)
-    })
-    .bind("127.0.0.1:8000")?
-    .run()
-    .await
+    // The logins are logged at the info level, `RUST_LOG` overrides it
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    // `USER_MANAGEMENT__AUTH__USERNAMES='["user"]'` overrides the users who can login
+    let settings = Settings::default().merge_env("USER_MANAGEMENT");
+
+    // The apps are initialized once and shared between the workers
+    let r = Reactor::shared()
+        .with_settings(settings)
+        .add_configured(AuthApp::default())
+        .add(BanningApp::default())
+        .add(SecondFactorApp::default())
+        .build()
+        .freeze();
+
+    // The background tasks and the scheduled jobs of the apps run on the main arbiter
+    r.start_tasks();
+
+    // The server doesn't keep the reactor alive, so the apps are finished once it stops
+    let weak = r.weak();
+    let server = HttpServer::new(move || App::new().configure_app(&weak).wrap_reactor(&weak))
+        .bind("127.0.0.1:8000")?
+        .run();
+    r.serve(server, Duration::from_secs(30)).await
 }
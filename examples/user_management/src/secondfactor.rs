@@ -8,7 +8,13 @@ pub struct SecondFactorApp {}
 
 impl App for SecondFactorApp {
     fn init(&mut self, r: &mut Registry) {
-        r.register_di(|auth_app: &mut AuthApp| {
+        // The second factor is only asked for when the project uses the auth app
+        r.register_di(|auth_app: Option<&mut AuthApp>| {
+            let auth_app = match auth_app {
+                Some(auth_app) => auth_app,
+                None => return,
+            };
+
             // After we authenticate the user
             auth_app.on_post_auth(|username, _| {
                 if username == "manager" {
@@ -1,16 +1,233 @@
+use std::any::{type_name, TypeId};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
 use actix_web::web::ServiceConfig;
 use downcast_rs::{impl_downcast, Downcast};
+use futures_util::future::{ready, LocalBoxFuture};
 
+use crate::cli::Commands;
+use crate::middleware::MiddlewareConfig;
+use crate::migrate::Migration;
 use crate::reactor::Registry;
 
 pub trait App: Downcast {
+    /// The apps this app depends on.
+    ///
+    /// The reactor checks that all of them are registered and initializes them before this app.
+    fn dependencies(&self) -> Vec<AppId> {
+        Vec::new()
+    }
+
     fn pre_init(&mut self) {}
     fn init(&mut self, _: &mut Registry) {}
     fn post_init(&mut self) {}
 
+    /// The prefix the web services of the app are mounted under, the project can override it by
+    /// adding the app with [`Reactor::add_at`](crate::Reactor::add_at).
+    ///
+    /// By default apps are mounted at the root.
+    fn mount_point(&self) -> &str {
+        ""
+    }
+
     fn configure_web(&self, _: &mut ServiceConfig) {}
 
+    /// Contributes actix middleware, either wrapping the whole web app or only the services of
+    /// this app under its mount point.
+    ///
+    /// Called once the config of the app is loaded, before the lifecycle starts, see
+    /// [`Reactor::middleware`](crate::Reactor::middleware) for the order the middleware are applied
+    /// in.
+    fn configure_middleware(&self, _: &mut MiddlewareConfig) {}
+
+    /// Adds the management commands of the app, like creating a user or seeding data, run by
+    /// [`SharedReactor::run_cli`](crate::SharedReactor::run_cli). Called once the reactor is built.
+    fn configure_commands(&self, _: &mut Commands) {}
+
+    /// The schema changes of the app in the order they are applied, see
+    /// [`Reactor::migrator`](crate::Reactor::migrator).
+    ///
+    /// Migrations are tracked by their name and the [`App::migrations_namespace`] of the app, so
+    /// applied migrations should never be renamed or edited.
+    fn migrations(&self) -> Vec<Migration> {
+        Vec::new()
+    }
+
+    /// The label the migrations of the app are tracked by, like `"auth"`, required for the apps
+    /// with migrations. It should never change once the migrations are applied, and each app or
+    /// named instance with migrations needs its own.
+    fn migrations_namespace(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Checks the health of the app, like pinging its database pool, see
+    /// [`Reactor::with_health_endpoint`](crate::Reactor::with_health_endpoint).
+    ///
+    /// Apps without a check are left out of the health report.
+    fn health(&self) -> Option<LocalBoxFuture<'_, Result<(), String>>> {
+        None
+    }
+
     fn finish(&mut self) {}
+
+    // The async variants of the lifecycle hooks, the reactor always calls these and by default
+    // they call their sync counterparts. Apps that need I/O during startup should override these
+    // and be built with `Reactor::build_async`.
+
+    fn pre_init_async(&mut self) -> LocalBoxFuture<'_, ()> {
+        self.pre_init();
+        Box::pin(ready(()))
+    }
+
+    fn init_async<'a>(&'a mut self, r: &'a mut Registry) -> LocalBoxFuture<'a, ()> {
+        self.init(r);
+        Box::pin(ready(()))
+    }
+
+    fn post_init_async(&mut self) -> LocalBoxFuture<'_, ()> {
+        self.post_init();
+        Box::pin(ready(()))
+    }
+
+    fn finish_async(&mut self) -> LocalBoxFuture<'_, ()> {
+        self.finish();
+        Box::pin(ready(()))
+    }
 }
 
 impl_downcast!(App);
+
+/// The trait object a reactor holds its apps as, `dyn App`, or `dyn App + Send + Sync` for the
+/// reactors which can be frozen, see [`Reactor::shared`](crate::Reactor::shared)
+pub trait AppObject: App {
+    /// Whether the reactors holding their apps as `Self` can be frozen, and so run the
+    /// background tasks of the apps
+    const SHARED: bool;
+
+    fn as_app(&self) -> &dyn App;
+
+    fn as_app_mut(&mut self) -> &mut dyn App;
+}
+
+impl AppObject for dyn App {
+    const SHARED: bool = false;
+
+    fn as_app(&self) -> &dyn App {
+        self
+    }
+
+    fn as_app_mut(&mut self) -> &mut dyn App {
+        self
+    }
+}
+
+impl AppObject for dyn App + Send + Sync {
+    const SHARED: bool = true;
+
+    fn as_app(&self) -> &dyn App {
+        self
+    }
+
+    fn as_app_mut(&mut self) -> &mut dyn App {
+        self
+    }
+}
+
+/// The apps a reactor holding them as `A` accepts
+pub trait IntoAppObject<A: ?Sized>: App {
+    fn into_object(self) -> Box<A>;
+}
+
+impl<T> IntoAppObject<dyn App> for T
+where
+    T: App,
+{
+    fn into_object(self) -> Box<dyn App> {
+        Box::new(self)
+    }
+}
+
+impl<T> IntoAppObject<dyn App + Send + Sync> for T
+where
+    T: App + Send + Sync,
+{
+    fn into_object(self) -> Box<dyn App + Send + Sync> {
+        Box::new(self)
+    }
+}
+
+/// Identifies an app by its type, and its name for the named instances
+#[derive(Clone, Copy)]
+pub struct AppId {
+    id: TypeId,
+    name: &'static str,
+    instance: Option<&'static str>,
+}
+
+impl AppId {
+    pub fn of<T>() -> Self
+    where
+        T: App,
+    {
+        Self::instance_of::<T>(None)
+    }
+
+    /// Identifies the instance of an app added with [`Reactor::add_named`](crate::Reactor::add_named)
+    pub fn named<T>(instance: &'static str) -> Self
+    where
+        T: App,
+    {
+        Self::instance_of::<T>(Some(instance))
+    }
+
+    pub(crate) fn instance_of<T>(instance: Option<&'static str>) -> Self
+    where
+        T: App,
+    {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            instance,
+        }
+    }
+
+    /// Whether both identify an instance of the same app
+    pub(crate) fn is_instance_of(&self, other: &AppId) -> bool {
+        self.id == other.id
+    }
+
+    /// The type name of the app
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The name of the instance, `None` for the apps added without a name
+    pub fn instance(&self) -> Option<&'static str> {
+        self.instance
+    }
+}
+
+impl PartialEq for AppId {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.instance == other.instance
+    }
+}
+
+impl Eq for AppId {}
+
+impl Hash for AppId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.instance.hash(state);
+    }
+}
+
+impl fmt::Debug for AppId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.instance {
+            Some(instance) => write!(f, "{}({:?})", self.name, instance),
+            None => f.write_str(self.name),
+        }
+    }
+}
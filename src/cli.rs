@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::future::Future;
+
+use futures_util::future::LocalBoxFuture;
+
+use crate::app::AppId;
+use crate::error::ReactorError;
+use crate::reactor::FrozenReactor;
+
+/// Runs a management command with the built reactor, it's implemented for the async closures
+/// taking the reactor and the parsed arguments
+pub trait CommandHandler: 'static {
+    fn run(
+        &self,
+        reactor: FrozenReactor,
+        args: Args,
+    ) -> LocalBoxFuture<'static, Result<(), String>>;
+}
+
+impl<F, Fut> CommandHandler for F
+where
+    F: Fn(FrozenReactor, Args) -> Fut + 'static,
+    Fut: Future<Output = Result<(), String>> + 'static,
+{
+    fn run(
+        &self,
+        reactor: FrozenReactor,
+        args: Args,
+    ) -> LocalBoxFuture<'static, Result<(), String>> {
+        Box::pin(self(reactor, args))
+    }
+}
+
+enum ArgKind {
+    Positional,
+    Option,
+    Flag,
+}
+
+struct ArgSpec {
+    name: &'static str,
+    help: &'static str,
+    kind: ArgKind,
+}
+
+impl ArgSpec {
+    fn usage(&self) -> String {
+        match self.kind {
+            ArgKind::Positional => format!("<{}>", self.name),
+            ArgKind::Option => format!("--{} <{}>", self.name, self.name),
+            ArgKind::Flag => format!("--{}", self.name),
+        }
+    }
+}
+
+/// The name and the arguments of a management command
+///
+/// ```ignore
+/// Command::new("createuser", "Creates a user")
+///     .arg("username", "The name of the user")
+///     .option("email", "The email of the user")
+///     .flag("admin", "Makes the user an admin")
+/// ```
+pub struct Command {
+    name: &'static str,
+    about: &'static str,
+    args: Vec<ArgSpec>,
+}
+
+impl Command {
+    pub fn new(name: &'static str, about: &'static str) -> Self {
+        Self {
+            name,
+            about,
+            args: Vec::new(),
+        }
+    }
+
+    /// A required positional argument, positional arguments are read in the order they are added
+    pub fn arg(self, name: &'static str, help: &'static str) -> Self {
+        self.spec(name, help, ArgKind::Positional)
+    }
+
+    /// An optional `--name value` argument
+    pub fn option(self, name: &'static str, help: &'static str) -> Self {
+        self.spec(name, help, ArgKind::Option)
+    }
+
+    /// A `--name` switch
+    pub fn flag(self, name: &'static str, help: &'static str) -> Self {
+        self.spec(name, help, ArgKind::Flag)
+    }
+
+    fn spec(mut self, name: &'static str, help: &'static str, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec { name, help, kind });
+        self
+    }
+
+    fn find(&self, name: &str) -> Option<&ArgSpec> {
+        self.args
+            .iter()
+            .find(|spec| spec.name == name && !matches!(spec.kind, ArgKind::Positional))
+    }
+
+    /// Parses the arguments following the name of the command
+    fn parse(&self, raw: &[String]) -> Result<Args, String> {
+        let mut args = Args::default();
+        let mut positional = self
+            .args
+            .iter()
+            .filter(|spec| matches!(spec.kind, ArgKind::Positional));
+        let mut raw = raw.iter();
+        while let Some(arg) = raw.next() {
+            let option = match arg.strip_prefix("--") {
+                Some(option) => option,
+                None => match positional.next() {
+                    Some(spec) => {
+                        args.values.insert(spec.name, arg.clone());
+                        continue;
+                    }
+                    None => return Err(format!("unexpected argument `{}`", arg)),
+                },
+            };
+            let (name, value) = match option.find('=') {
+                Some(idx) => (&option[..idx], Some(option[idx + 1..].to_string())),
+                None => (option, None),
+            };
+            let spec = self
+                .find(name)
+                .ok_or_else(|| format!("unknown option `--{}`", name))?;
+            match (&spec.kind, value) {
+                (ArgKind::Flag, None) => args.flags.push(spec.name),
+                (ArgKind::Flag, Some(_)) => {
+                    return Err(format!("`--{}` doesn't take a value", name))
+                }
+                (_, Some(value)) => {
+                    args.values.insert(spec.name, value);
+                }
+                (_, None) => {
+                    let value = raw
+                        .next()
+                        .ok_or_else(|| format!("`--{}` needs a value", name))?;
+                    args.values.insert(spec.name, value.clone());
+                }
+            }
+        }
+        match positional.next() {
+            Some(spec) => Err(format!("missing argument `<{}>`", spec.name)),
+            None => Ok(args),
+        }
+    }
+
+    fn help(&self, program: &str) -> String {
+        let mut usage = format!("Usage: {} {}", program, self.name);
+        for spec in self.args.iter() {
+            match spec.kind {
+                ArgKind::Positional => write!(usage, " {}", spec.usage()),
+                _ => write!(usage, " [{}]", spec.usage()),
+            }
+            .unwrap();
+        }
+
+        let mut help = format!("{}\n\n{}\n", self.about, usage);
+        if !self.args.is_empty() {
+            help.push_str("\nArguments:\n");
+            let usages: Vec<_> = self.args.iter().map(ArgSpec::usage).collect();
+            let width = usages.iter().map(String::len).max().unwrap_or(0);
+            for (usage, spec) in usages.iter().zip(self.args.iter()) {
+                writeln!(help, "  {:width$}  {}", usage, spec.help, width = width).unwrap();
+            }
+        }
+        help
+    }
+}
+
+/// The parsed arguments of a management command
+#[derive(Debug, Default)]
+pub struct Args {
+    values: HashMap<&'static str, String>,
+    flags: Vec<&'static str>,
+}
+
+impl Args {
+    /// The value of a positional argument or an option, positional arguments are always present
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Parses the value of a positional argument or an option
+    pub fn parse<T>(&self, name: &str) -> Result<Option<T>, String>
+    where
+        T: std::str::FromStr,
+        T::Err: fmt::Display,
+    {
+        self.get(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|err| format!("invalid value of `{}`: {}", name, err))
+            })
+            .transpose()
+    }
+
+    /// Whether a flag is passed
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+}
+
+pub(crate) struct Registered {
+    app: AppId,
+    command: Command,
+    handler: Box<dyn CommandHandler>,
+}
+
+impl Registered {
+    pub(crate) fn name(&self) -> &'static str {
+        self.command.name
+    }
+}
+
+/// The management commands of an app, filled by [`App::configure_commands`](crate::App::configure_commands)
+pub struct Commands {
+    app: AppId,
+    pub(crate) commands: Vec<Registered>,
+}
+
+impl Commands {
+    pub(crate) fn new(app: AppId) -> Self {
+        Self {
+            app,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Adds a command, it's run with the built reactor and its parsed arguments:
+    ///
+    /// ```ignore
+    /// cmds.add(Command::new("createuser", "Creates a user").arg("username", "The name"),
+    ///     |reactor: FrozenReactor, args: Args| async move {
+    ///         let auth = reactor.get::<AuthApp>().unwrap();
+    ///         auth.create(args.get("username").unwrap())
+    ///     });
+    /// ```
+    pub fn add<H>(&mut self, command: Command, handler: H) -> &mut Self
+    where
+        H: CommandHandler,
+    {
+        self.commands.push(Registered {
+            app: self.app,
+            command,
+            handler: Box::new(handler),
+        });
+        self
+    }
+}
+
+/// What the command line asks for
+pub(crate) enum Invocation<'a> {
+    Help(String),
+    Run(&'a Registered, Args),
+}
+
+/// The commands of all the apps, in the order of the apps
+pub(crate) struct Cli {
+    program: String,
+    commands: Vec<Registered>,
+}
+
+impl Cli {
+    pub(crate) fn new(program: String, commands: Vec<Registered>) -> Result<Self, CliError> {
+        for (idx, registered) in commands.iter().enumerate() {
+            let name = registered.command.name;
+            if let Some(prev) = commands[..idx]
+                .iter()
+                .find(|prev| prev.command.name == name)
+            {
+                return Err(CliError::DuplicateCommand {
+                    command: name.to_string(),
+                    apps: (prev.app.name(), registered.app.name()),
+                });
+            }
+        }
+        Ok(Self { program, commands })
+    }
+
+    fn find(&self, name: &str) -> Result<&Registered, CliError> {
+        self.commands
+            .iter()
+            .find(|registered| registered.command.name == name)
+            .ok_or_else(|| CliError::UnknownCommand(name.to_string()))
+    }
+
+    pub(crate) fn parse(&self, args: &[String]) -> Result<Invocation<'_>, CliError> {
+        let (name, rest) = match args.split_first() {
+            Some((name, rest)) => (name.as_str(), rest),
+            None => return Ok(Invocation::Help(self.help())),
+        };
+        match (name, rest) {
+            ("help" | "--help" | "-h", []) => Ok(Invocation::Help(self.help())),
+            ("help", [command]) => Ok(Invocation::Help(
+                self.find(command)?.command.help(&self.program),
+            )),
+            (_, [flag]) if flag == "--help" || flag == "-h" => Ok(Invocation::Help(
+                self.find(name)?.command.help(&self.program),
+            )),
+            _ => {
+                let registered = self.find(name)?;
+                let args =
+                    registered
+                        .command
+                        .parse(rest)
+                        .map_err(|message| CliError::InvalidArgs {
+                            command: name.to_string(),
+                            message,
+                        })?;
+                Ok(Invocation::Run(registered, args))
+            }
+        }
+    }
+
+    pub(crate) async fn run(
+        registered: &Registered,
+        reactor: FrozenReactor,
+        args: Args,
+    ) -> Result<(), CliError> {
+        registered
+            .handler
+            .run(reactor, args)
+            .await
+            .map_err(|message| CliError::Failed {
+                command: registered.command.name.to_string(),
+                message,
+            })
+    }
+
+    /// Lists the commands grouped by the apps which added them
+    pub(crate) fn help(&self) -> String {
+        let mut help = format!("Usage: {} <command> [args]\n\nCommands:\n", self.program);
+        let width = self
+            .commands
+            .iter()
+            .map(|registered| registered.command.name.len())
+            .max()
+            .unwrap_or(0);
+        let mut app = None;
+        for registered in self.commands.iter() {
+            if app != Some(registered.app) {
+                app = Some(registered.app);
+                match registered.app.instance() {
+                    Some(instance) => {
+                        writeln!(help, "  {} ({})", registered.app.name(), instance)
+                    }
+                    None => writeln!(help, "  {}", registered.app.name()),
+                }
+                .unwrap();
+            }
+            let command = &registered.command;
+            writeln!(
+                help,
+                "    {:width$}  {}",
+                command.name,
+                command.about,
+                width = width
+            )
+            .unwrap();
+        }
+        write!(
+            help,
+            "\nRun `{} help <command>` for the arguments of a command.",
+            self.program
+        )
+        .unwrap();
+        help
+    }
+}
+
+/// The reasons a management command fails, see [`SharedReactor::run_cli`](crate::SharedReactor::run_cli)
+#[derive(Debug)]
+pub enum CliError {
+    UnknownCommand(String),
+    /// Two apps add commands with the same name
+    DuplicateCommand {
+        command: String,
+        apps: (&'static str, &'static str),
+    },
+    InvalidArgs {
+        command: String,
+        message: String,
+    },
+    /// The reactor failed to build before running the command
+    Build(ReactorError),
+    /// The command returned an error
+    Failed {
+        command: String,
+        message: String,
+    },
+    /// The command kept a clone of the frozen reactor, so the apps couldn't be finished, along
+    /// with the result of the command
+    Unfinished {
+        command: String,
+        result: Result<(), Box<CliError>>,
+    },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownCommand(command) => write!(
+                f,
+                "unknown command `{}`, run `help` to list the commands",
+                command
+            ),
+            CliError::DuplicateCommand { command, apps } => write!(
+                f,
+                "command `{}` is added by both `{}` and `{}`",
+                command, apps.0, apps.1
+            ),
+            CliError::InvalidArgs { command, message } => write!(
+                f,
+                "{}, run `help {}` for the arguments of the command",
+                message, command
+            ),
+            CliError::Build(err) => err.fmt(f),
+            CliError::Failed { command, message } => {
+                write!(f, "command `{}` failed: {}", command, message)
+            }
+            CliError::Unfinished { command, result } => {
+                if let Err(err) = result {
+                    write!(f, "{}, and ", err)?;
+                }
+                write!(
+                    f,
+                    "command `{}` kept the reactor alive, the apps are not finished",
+                    command
+                )
+            }
+        }
+    }
+}
+
+impl Error for CliError {}
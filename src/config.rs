@@ -0,0 +1,364 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+
+use crate::app::App;
+
+/// An app which receives a typed config, loaded from its own section of the project settings
+///
+/// The app has to be added with [`Reactor::add_configured`](crate::Reactor::add_configured).
+pub trait Configurable: App {
+    type Config: DeserializeOwned;
+
+    /// The section of the settings holding the config of the app
+    const NAMESPACE: &'static str;
+
+    /// Called with the config of the app before `pre_init`
+    fn configure(&mut self, config: Self::Config);
+}
+
+/// Loads the config of a configurable app from the settings, named instances read the table named
+/// after them in the section of the app, which is left out of the config of the unnamed instance
+/// along with the tables of the other named instances listed
+pub(crate) type Loader = fn(&mut dyn App, &Settings, Option<&str>, &[&str]) -> Result<(), String>;
+
+pub(crate) fn loader<T>() -> Loader
+where
+    T: Configurable,
+{
+    |app, settings, instance, named| {
+        let config = match instance {
+            Some(instance) => settings.lookup::<T::Config>(&[T::NAMESPACE, instance], &[])?,
+            None => settings.lookup::<T::Config>(&[T::NAMESPACE], named)?,
+        };
+        if let Some(app) = app.downcast_mut::<T>() {
+            app.configure(config);
+        }
+        Ok(())
+    }
+}
+
+/// The settings of a project, each app reads its config from a namespaced section
+///
+/// Settings are loaded from TOML or JSON files and the environment, later sources override the
+/// values of the earlier ones:
+///
+/// ```ignore
+/// let settings = Settings::from_file("config.toml")?
+///     .merge_file("local.toml")?
+///     .merge_env("APP");
+/// // `APP__AUTH__MAX_ATTEMPTS=5` sets `max_attempts` in the `auth` section
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Settings {
+    root: Map<String, Value>,
+}
+
+impl Settings {
+    /// Loads the settings from a file, the format is picked by its `.toml` or `.json` extension
+    pub fn from_file<P>(path: P) -> Result<Self, ConfigError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            _ => Err(ConfigError::Format(path.display().to_string())),
+        }
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let value: toml::Value =
+            toml::from_str(content).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        let value =
+            serde_json::to_value(value).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        Self::from_value(value)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, ConfigError> {
+        let value =
+            serde_json::from_str(content).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        Self::from_value(value)
+    }
+
+    fn from_value(value: Value) -> Result<Self, ConfigError> {
+        match value {
+            Value::Object(root) => Ok(Self { root }),
+            _ => Err(ConfigError::Parse(
+                "the settings should be a table".to_string(),
+            )),
+        }
+    }
+
+    /// Overrides the settings with the ones loaded from a file, see [`Settings::from_file`]
+    pub fn merge_file<P>(self, path: P) -> Result<Self, ConfigError>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(self.merge(Self::from_file(path)?))
+    }
+
+    /// Overrides the settings with the values of `other`, the tables present in both are merged
+    /// key by key
+    pub fn merge(mut self, other: Settings) -> Self {
+        merge(&mut self.root, other.root);
+        self
+    }
+
+    /// Overrides the settings using the environment variables starting with `prefix`.
+    ///
+    /// The rest of the name is the path of the value, separated by double underscores and
+    /// lowercased, so `PREFIX__AUTH__MAX_ATTEMPTS` sets `auth.max_attempts`. Values are kept as
+    /// strings, and parsed only where the config expects another type, like a number, a boolean
+    /// or a JSON array.
+    pub fn merge_env(mut self, prefix: &str) -> Self {
+        let prefix = format!("{}__", prefix);
+        for (name, value) in std::env::vars() {
+            let path = match name.strip_prefix(&prefix) {
+                Some(path) if !path.is_empty() => path.to_lowercase(),
+                _ => continue,
+            };
+            self.set(path.split("__"), Value::String(value));
+        }
+        self
+    }
+
+    fn set<'a, I>(&mut self, path: I, value: Value)
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut path = path.peekable();
+        let mut table = &mut self.root;
+        while let Some(key) = path.next() {
+            if path.peek().is_none() {
+                table.insert(key.to_string(), value);
+                return;
+            }
+            let entry = table
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            table = match entry {
+                Value::Object(table) => table,
+                _ => unreachable!(),
+            };
+        }
+    }
+
+    /// Deserializes a section of the settings, a missing section is read as an empty table
+    pub fn section<T>(&self, namespace: &str) -> Result<T, String>
+    where
+        T: DeserializeOwned,
+    {
+        self.lookup(&[namespace], &[])
+    }
+
+    /// Deserializes the table at `path` without its `skipped` keys, a missing table is read as an
+    /// empty one
+    fn lookup<T>(&self, path: &[&str], skipped: &[&str]) -> Result<T, String>
+    where
+        T: DeserializeOwned,
+    {
+        let mut table = Some(&self.root);
+        let mut value = None;
+        for key in path {
+            value = table.and_then(|table| table.get(*key));
+            table = value.and_then(Value::as_object);
+        }
+        let mut value = value.cloned().unwrap_or_else(|| Value::Object(Map::new()));
+        if let Value::Object(table) = &mut value {
+            for key in skipped {
+                table.remove(*key);
+            }
+        }
+        T::deserialize(Lenient(value)).map_err(|err| err.to_string())
+    }
+}
+
+fn merge(table: &mut Map<String, Value>, other: Map<String, Value>) {
+    for (key, value) in other {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Object(table)), Value::Object(other)) => merge(table, other),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Deserializes the settings, parsing the strings set by the environment variables where another
+/// type is expected
+struct Lenient(Value);
+
+impl Lenient {
+    fn parse(self) -> Self {
+        match self.0 {
+            Value::String(value) => {
+                Lenient(serde_json::from_str(&value).unwrap_or(Value::String(value)))
+            }
+            value => Lenient(value),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for Lenient {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_and_deserialize {
+    ($($method:ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.parse().deserialize_any(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Array(values) => {
+                visitor.visit_seq(SeqDeserializer::new(values.into_iter().map(Lenient)))
+            }
+            Value::Object(table) => visitor.visit_map(MapDeserializer::new(
+                table.into_iter().map(|(key, value)| (key, Lenient(value))),
+            )),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    parse_and_deserialize!(
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_seq,
+        deserialize_map
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Lenient(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V>(self, _: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.parse().deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.parse().deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.parse().deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct identifier ignored_any
+    }
+}
+
+/// Failure to load the settings of a project
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    /// The file has an unknown extension
+    Format(String),
+    Parse(String),
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "failed to read the settings: {}", err),
+            ConfigError::Format(path) => write!(f, "unknown settings format of `{}`", path),
+            ConfigError::Parse(message) => write!(f, "failed to parse the settings: {}", message),
+        }
+    }
+}
+
+impl Error for ConfigError {}
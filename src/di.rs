@@ -1,15 +1,364 @@
+use std::fmt;
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 
-use crate::reactor::Reactor;
+use futures_util::future::{ready, LocalBoxFuture};
 
-trait Extractable<'a> {
-    type Result;
+use crate::app::AppObject;
+use crate::app::{App, AppId};
+use crate::reactor::Entry;
 
-    fn extract(r: &'a mut Reactor) -> Option<Self::Result>;
+/// Values which can be returned from a DI hook, a hook can either return nothing or a `Result`
+pub trait HookResult {
+    fn into_result(self) -> Result<(), String>;
+}
+
+impl HookResult for () {
+    fn into_result(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<E> HookResult for Result<(), E>
+where
+    E: fmt::Display,
+{
+    fn into_result(self) -> Result<(), String> {
+        self.map_err(|err| err.to_string())
+    }
+}
+
+pub enum HookError {
+    Missing(AppId),
+    Failed(String),
+}
+
+/// Describes an app requested by a DI hook
+#[derive(Clone, Copy, Debug)]
+pub struct Param {
+    pub(crate) app: AppId,
+    pub(crate) mutable: bool,
+    pub(crate) optional: bool,
+}
+
+impl Param {
+    /// Whether both params can't be handed to the same hook
+    pub(crate) fn conflicts(&self, other: &Param) -> bool {
+        self.app == other.app && (self.mutable || other.mutable)
+    }
+}
+
+/// Names a DI hook by the apps it asks for, like `(&mut Auth, Option<&Mail>)`, named instances
+/// are written as `&Auth@staff`
+pub(crate) fn signature(params: &[Param]) -> String {
+    let params: Vec<_> = params
+        .iter()
+        .map(|param| {
+            let reference = if param.mutable { "&mut " } else { "&" };
+            let name = match param.app.instance() {
+                Some(instance) => format!("{}{}@{}", reference, param.app.name(), instance),
+                None => format!("{}{}", reference, param.app.name()),
+            };
+            match param.optional {
+                true => format!("Option<{}>", name),
+                false => name,
+            }
+        })
+        .collect();
+    format!("({})", params.join(", "))
+}
+
+enum Slot<'a> {
+    Free(&'a mut dyn App),
+    Shared(&'a dyn App),
+    Taken,
+}
+
+/// The apps of a reactor lent to a DI hook while it runs
+///
+/// Each app can either be borrowed mutably once, or be shared any number of times.
+pub struct Loan<'a> {
+    apps: Vec<(AppId, Slot<'a>)>,
+}
+
+impl<'a> Loan<'a> {
+    pub(crate) fn new<A>(apps: &'a mut [Entry<A>]) -> Self
+    where
+        A: ?Sized + AppObject,
+    {
+        Self {
+            apps: apps
+                .iter_mut()
+                .map(|entry| (entry.own_id(), Slot::Free(entry.app.as_app_mut())))
+                .collect(),
+        }
+    }
+
+    fn slot(&mut self, id: AppId) -> Option<&mut Slot<'a>> {
+        self.apps
+            .iter_mut()
+            .find(|(app, _)| *app == id)
+            .map(|(_, slot)| slot)
+    }
+
+    /// Takes the app out of the loan, returns `None` if it is not registered or is already
+    /// borrowed
+    fn take<T>(&mut self, id: AppId) -> Option<&'a mut T>
+    where
+        T: App,
+    {
+        let slot = self.slot(id)?;
+        match std::mem::replace(slot, Slot::Taken) {
+            Slot::Free(app) => app.downcast_mut(),
+            other => {
+                *slot = other;
+                None
+            }
+        }
+    }
+
+    /// Shares the app, returns `None` if it is not registered or is already borrowed mutably
+    fn share<T>(&mut self, id: AppId) -> Option<&'a T>
+    where
+        T: App,
+    {
+        let slot = self.slot(id)?;
+        let app: &'a dyn App = match std::mem::replace(slot, Slot::Taken) {
+            Slot::Free(app) => app,
+            Slot::Shared(app) => app,
+            Slot::Taken => return None,
+        };
+        *slot = Slot::Shared(app);
+        app.downcast_ref()
+    }
+}
+
+/// A parameter of a DI hook
+///
+/// It's implemented for `&mut T`, `&T` and their `Option` forms where `T` is an app, the
+/// optional forms receive `None` when the app is not registered in the reactor. `'a` is the
+/// lifetime of the loan the parameter is fetched from.
+///
+/// Structs of such parameters can implement it with [`derive(Inject)`](crate::Inject), to ask
+/// for more apps than a hook can take as parameters.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be fetched as the parameter of a DI hook",
+    label = "not a reference to an app",
+    note = "the parameters are `&T`, `&mut T`, their `Option` and `Named` forms, or `derive(Inject)` structs"
+)]
+pub trait Dependency<'a> {
+    type Item;
+
+    fn params() -> Vec<Param>;
+
+    fn fetch(loan: &mut Loan<'a>) -> Result<Self::Item, AppId>;
+}
+
+/// A parameter which can target a named instance of an app, see [`Named`]
+///
+/// Every `InstanceDependency` is a [`Dependency`] targeting the unnamed instance.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be fetched as the parameter of a DI hook",
+    label = "not a reference to an app",
+    note = "the parameters are `&T`, `&mut T`, their `Option` and `Named` forms, or `derive(Inject)` structs"
+)]
+pub trait InstanceDependency<'a> {
+    type Item;
+
+    fn instance_params(instance: Option<&'static str>) -> Vec<Param>;
+
+    fn fetch_instance(
+        loan: &mut Loan<'a>,
+        instance: Option<&'static str>,
+    ) -> Result<Self::Item, AppId>;
+}
+
+impl<'a, P> Dependency<'a> for P
+where
+    P: InstanceDependency<'a>,
+{
+    type Item = P::Item;
+
+    fn params() -> Vec<Param> {
+        P::instance_params(None)
+    }
+
+    fn fetch(loan: &mut Loan<'a>) -> Result<Self::Item, AppId> {
+        P::fetch_instance(loan, None)
+    }
+}
+
+impl<'a, T> InstanceDependency<'a> for &mut T
+where
+    T: App,
+{
+    type Item = &'a mut T;
+
+    fn instance_params(instance: Option<&'static str>) -> Vec<Param> {
+        vec![Param {
+            app: AppId::instance_of::<T>(instance),
+            mutable: true,
+            optional: false,
+        }]
+    }
+
+    fn fetch_instance(
+        loan: &mut Loan<'a>,
+        instance: Option<&'static str>,
+    ) -> Result<Self::Item, AppId> {
+        let id = AppId::instance_of::<T>(instance);
+        loan.take(id).ok_or(id)
+    }
+}
+
+impl<'a, T> InstanceDependency<'a> for &T
+where
+    T: App,
+{
+    type Item = &'a T;
+
+    fn instance_params(instance: Option<&'static str>) -> Vec<Param> {
+        vec![Param {
+            app: AppId::instance_of::<T>(instance),
+            mutable: false,
+            optional: false,
+        }]
+    }
+
+    fn fetch_instance(
+        loan: &mut Loan<'a>,
+        instance: Option<&'static str>,
+    ) -> Result<Self::Item, AppId> {
+        let id = AppId::instance_of::<T>(instance);
+        loan.share(id).ok_or(id)
+    }
+}
+
+impl<'a, T> InstanceDependency<'a> for Option<&mut T>
+where
+    T: App,
+{
+    type Item = Option<&'a mut T>;
+
+    fn instance_params(instance: Option<&'static str>) -> Vec<Param> {
+        vec![Param {
+            app: AppId::instance_of::<T>(instance),
+            mutable: true,
+            optional: true,
+        }]
+    }
+
+    fn fetch_instance(
+        loan: &mut Loan<'a>,
+        instance: Option<&'static str>,
+    ) -> Result<Self::Item, AppId> {
+        let id = AppId::instance_of::<T>(instance);
+        Ok(loan.take(id))
+    }
+}
+
+impl<'a, T> InstanceDependency<'a> for Option<&T>
+where
+    T: App,
+{
+    type Item = Option<&'a T>;
+
+    fn instance_params(instance: Option<&'static str>) -> Vec<Param> {
+        vec![Param {
+            app: AppId::instance_of::<T>(instance),
+            mutable: false,
+            optional: true,
+        }]
+    }
+
+    fn fetch_instance(
+        loan: &mut Loan<'a>,
+        instance: Option<&'static str>,
+    ) -> Result<Self::Item, AppId> {
+        let id = AppId::instance_of::<T>(instance);
+        Ok(loan.share(id))
+    }
+}
+
+/// The name of an app instance, used to ask for it in DI hooks
+///
+/// ```ignore
+/// struct Staff;
+///
+/// impl InstanceName for Staff {
+///     const NAME: &'static str = "staff";
+/// }
+/// ```
+pub trait InstanceName: 'static {
+    const NAME: &'static str;
+}
+
+/// A DI hook parameter targeting a named instance of an app, it dereferences to the wrapped
+/// parameter:
+///
+/// ```ignore
+/// r.register_di(|staff: Named<Staff, &mut AuthApp>, customers: Named<Customers, &AuthApp>| {
+///     // ...
+/// });
+/// ```
+pub struct Named<N, P> {
+    value: P,
+    _name: PhantomData<N>,
+}
+
+impl<N, P> Named<N, P> {
+    pub fn into_inner(self) -> P {
+        self.value
+    }
+}
+
+impl<N, P> Deref for Named<N, P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.value
+    }
+}
+
+impl<N, P> DerefMut for Named<N, P> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.value
+    }
+}
+
+impl<'a, N, P> Dependency<'a> for Named<N, P>
+where
+    N: InstanceName,
+    P: InstanceDependency<'a>,
+{
+    type Item = Named<N, P::Item>;
+
+    fn params() -> Vec<Param> {
+        P::instance_params(Some(N::NAME))
+    }
+
+    fn fetch(loan: &mut Loan<'a>) -> Result<Self::Item, AppId> {
+        let value = P::fetch_instance(loan, Some(N::NAME))?;
+        Ok(Named {
+            value,
+            _name: PhantomData,
+        })
+    }
 }
 
 pub trait InjectFactory<P> {
-    fn run(&self, r: &mut Reactor);
+    fn run(&self, loan: Loan<'_>) -> Result<(), HookError>;
+
+    /// The apps this hook depends on
+    fn params() -> Vec<Param>;
+}
+
+/// A DI hook returning a future, the future can borrow the apps the hook asks for
+pub trait AsyncInjectFactory<P> {
+    fn run<'r>(&'r self, loan: Loan<'r>) -> LocalBoxFuture<'r, Result<(), HookError>>;
+
+    /// The apps this hook depends on
+    fn params() -> Vec<Param>;
 }
 
 pub struct Injectable<F, P> {
@@ -26,39 +375,115 @@ impl<F, P> Injectable<F, P> {
     }
 }
 
+pub struct AsyncInjectable<F, P> {
+    f: F,
+    _marker: PhantomData<P>,
+}
+
+impl<F, P> AsyncInjectable<F, P> {
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            _marker: PhantomData,
+        }
+    }
+}
+
 pub trait Injector {
-    fn run(&self, r: &mut Reactor);
+    fn run<'r>(&'r self, loan: Loan<'r>) -> LocalBoxFuture<'r, Result<(), HookError>>;
 }
 
 impl<F, P> Injector for Injectable<F, P>
 where
     F: InjectFactory<P>,
 {
-    fn run(&self, r: &mut Reactor) {
-        self.f.run(r)
+    fn run<'r>(&'r self, loan: Loan<'r>) -> LocalBoxFuture<'r, Result<(), HookError>> {
+        Box::pin(ready(self.f.run(loan)))
+    }
+}
+
+impl<F, P> Injector for AsyncInjectable<F, P>
+where
+    F: AsyncInjectFactory<P>,
+{
+    fn run<'r>(&'r self, loan: Loan<'r>) -> LocalBoxFuture<'r, Result<(), HookError>> {
+        self.f.run(loan)
     }
 }
 
 mod private {
     #![allow(non_snake_case)]
 
-    use std::any::{type_name, TypeId};
+    use std::future::Future;
+
+    use futures_util::future::LocalBoxFuture;
 
-    use super::Extractable;
-    use super::InjectFactory;
-    use crate::app::App;
-    use crate::reactor::Reactor;
+    use super::{
+        AsyncInjectFactory, Dependency, HookError, HookResult, InjectFactory, Loan, Param,
+    };
+
+    /// An async hook called with the apps borrowed for `'a`, its future can keep them borrowed
+    pub trait AsyncFn<'a, Args, R> {
+        fn call(&self, args: Args) -> LocalBoxFuture<'a, R>;
+    }
 
+    // The first `Fn` bound lets the compiler infer the parameter types from the closure, the
+    // second one is the one actually called with the borrowed apps.
     macro_rules! impl_factory_for_fn {
         ($($param:ident),*) => {
-            impl<F, $($param),*> InjectFactory<($($param),*,)> for F
+            impl<F, R, $($param),*> InjectFactory<fn($($param),*) -> R> for F
             where
-                F: Fn($(&mut $param),*),
-                $($param: App),*
+                F: Fn($($param),*) -> R,
+                F: for<'a> Fn($(<$param as Dependency<'a>>::Item),*) -> R,
+                R: HookResult,
+                $($param: for<'a> Dependency<'a>),*
             {
-                fn run(&self, r: &mut Reactor) {
-                    let ($($param),*,) = <($($param),*,)>::extract(r).unwrap();
-                    self($($param),*)
+                fn run(&self, mut loan: Loan<'_>) -> Result<(), HookError> {
+                    $(
+                        let $param = $param::fetch(&mut loan).map_err(HookError::Missing)?;
+                    )*
+                    self($($param),*).into_result().map_err(HookError::Failed)
+                }
+
+                fn params() -> Vec<Param> {
+                    let mut params = Vec::new();
+                    $(params.extend($param::params());)*
+                    params
+                }
+            }
+
+            impl<'a, F, Fut, R, $($param),*> AsyncFn<'a, ($($param,)*), R> for F
+            where
+                F: Fn($($param),*) -> Fut,
+                Fut: Future<Output = R> + 'a,
+            {
+                fn call(&self, ($($param,)*): ($($param,)*)) -> LocalBoxFuture<'a, R> {
+                    Box::pin(self($($param),*))
+                }
+            }
+
+            impl<F, Fut, R, $($param),*> AsyncInjectFactory<fn($($param),*) -> R> for F
+            where
+                F: Fn($($param),*) -> Fut,
+                Fut: Future<Output = R>,
+                F: for<'a> AsyncFn<'a, ($(<$param as Dependency<'a>>::Item,)*), R>,
+                R: HookResult,
+                $($param: for<'a> Dependency<'a>),*
+            {
+                fn run<'r>(&'r self, mut loan: Loan<'r>) -> LocalBoxFuture<'r, Result<(), HookError>> {
+                    Box::pin(async move {
+                        $(
+                            let $param = $param::fetch(&mut loan).map_err(HookError::Missing)?;
+                        )*
+                        let fut = AsyncFn::call(self, ($($param,)*));
+                        fut.await.into_result().map_err(HookError::Failed)
+                    })
+                }
+
+                fn params() -> Vec<Param> {
+                    let mut params = Vec::new();
+                    $(params.extend($param::params());)*
+                    params
                 }
             }
         };
@@ -76,62 +501,4 @@ mod private {
     impl_factory_for_fn!(P1, P2, P3, P4, P5, P6, P7, P8, P9, P10);
     impl_factory_for_fn!(P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11);
     impl_factory_for_fn!(P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12);
-
-    macro_rules! impl_extractable {
-        ($num:expr, $gen1:ident, $($gen:ident => $count:expr),*) => {
-            impl<'a, $gen1, $($gen),*> Extractable<'a> for ($gen1, $($gen),*)
-            where
-                $gen1: App + 'static,
-                $($gen : App + 'static),*
-            {
-                type Result = (&'a mut $gen1, $(&'a mut $gen),*);
-
-                fn extract(r: &'a mut Reactor) -> Option<Self::Result> {
-                    let mut res: [Option<&mut Box<dyn App>>; $num] = Default::default();
-                    for (id, app) in r.apps.iter_mut() {
-                        if *id == TypeId::of::<$gen1>() {
-                            res[0] = Some(app);
-                        }
-                        $(
-                            else if *id == TypeId::of::<$gen>() {
-                                res[$count] = Some(app);
-                            }
-                        )*
-                    }
-
-                    let [$gen1, $($gen),*] = res;
-
-                    Some((
-                        $gen1
-                            .unwrap_or_else(||
-                                panic!(format!("Dependency {:?} is not registered in app or is used twice!", type_name::<$gen1>()))
-                            )
-                            .downcast_mut()
-                            .unwrap(),
-                        $(
-                            $gen
-                                .unwrap_or_else(||
-                                    panic!(format!("Dependency {:?} is not registered in app or is used twice!", type_name::<$gen>()))
-                                )
-                                .downcast_mut()
-                                .unwrap()
-                        ),*
-                    ))
-                }
-            }
-        };
-    }
-
-    impl_extractable!(1, P1,);
-    impl_extractable!(2, P1, P2 => 1);
-    impl_extractable!(3, P1, P2 => 1, P3 => 2);
-    impl_extractable!(4, P1, P2 => 1, P3 => 2, P4 => 3);
-    impl_extractable!(5, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4);
-    impl_extractable!(6, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4, P6 => 5);
-    impl_extractable!(7, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4, P6 => 5, P7 => 6);
-    impl_extractable!(8, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4, P6 => 5, P7 => 6, P8 => 7);
-    impl_extractable!(9, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4, P6 => 5, P7 => 6, P8 => 7, P9 => 8);
-    impl_extractable!(10, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4, P6 => 5, P7 => 6, P8 => 7, P9 => 8, P10 => 9);
-    impl_extractable!(11, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4, P6 => 5, P7 => 6, P8 => 7, P9 => 8, P10 => 9, P11 => 10);
-    impl_extractable!(12, P1, P2 => 1, P3 => 2, P4 => 3, P5 => 4, P6 => 5, P7 => 6, P8 => 7, P9 => 8, P10 => 9, P11 => 10, P12 => 11);
 }
@@ -0,0 +1,220 @@
+use std::error::Error;
+use std::fmt;
+
+/// A single problem found while building a reactor
+#[derive(Debug)]
+pub enum BuildError {
+    /// An app declares a dependency on an app which is not registered, `instance` is set for the
+    /// named instances
+    MissingApp {
+        app: &'static str,
+        dependency: &'static str,
+        instance: Option<&'static str>,
+    },
+    /// An app is replaced while it's not registered
+    NotRegistered { app: &'static str },
+    /// Apps depending on each other, the first app is repeated at the end
+    Cycle(Vec<&'static str>),
+    /// The config section of an app failed to deserialize
+    InvalidConfig { app: &'static str, message: String },
+    /// A DI hook asks for an app which is not registered
+    MissingParam {
+        app: &'static str,
+        hook: String,
+        param: &'static str,
+        instance: Option<&'static str>,
+    },
+    /// A DI hook asks for an app which is replaced by a stand-in of another type
+    ReplacedParam {
+        app: &'static str,
+        hook: String,
+        param: &'static str,
+        instance: Option<&'static str>,
+        stand_in: &'static str,
+    },
+    /// A DI hook asks for the same app more than once, while borrowing it mutably
+    DuplicateParam {
+        app: &'static str,
+        hook: String,
+        param: &'static str,
+    },
+    /// A lifecycle step or a DI hook of an app had to wait while the reactor was built
+    /// synchronously
+    Blocked {
+        app: &'static str,
+        step: &'static str,
+    },
+    /// An async DI hook was registered while the reactor was built synchronously, no hook is run
+    /// in this case
+    BlockedHook { app: &'static str, hook: String },
+    /// A DI hook returned an error
+    HookFailed {
+        app: &'static str,
+        hook: String,
+        message: String,
+    },
+    /// An app mounted at the root contributes scoped middleware
+    UnmountedMiddleware { app: &'static str },
+    /// An app registers a background task on a reactor which can't be frozen to run it, see
+    /// [`Reactor::shared`](crate::Reactor::shared)
+    UnsharedTask { app: &'static str },
+    /// An app has migrations but no namespace to track them by
+    UnlabeledMigrations { app: &'static str },
+    /// Two apps track their migrations under the same namespace
+    DuplicateNamespace {
+        namespace: &'static str,
+        apps: (&'static str, &'static str),
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingApp {
+                app,
+                dependency,
+                instance,
+            } => write!(
+                f,
+                "`{}` depends on {} which is not registered",
+                app,
+                Target(dependency, *instance)
+            ),
+            BuildError::NotRegistered { app } => {
+                write!(f, "`{}` can't be replaced as it is not registered", app)
+            }
+            BuildError::Cycle(apps) => write!(f, "dependency cycle: {}", apps.join(" -> ")),
+            BuildError::InvalidConfig { app, message } => {
+                write!(f, "invalid config of `{}`: {}", app, message)
+            }
+            BuildError::MissingParam {
+                app,
+                hook,
+                param,
+                instance,
+            } => write!(
+                f,
+                "hook `{}` of `{}` depends on {} which is not registered",
+                hook,
+                app,
+                Target(param, *instance)
+            ),
+            BuildError::ReplacedParam {
+                app,
+                hook,
+                param,
+                instance,
+                stand_in,
+            } => write!(
+                f,
+                "hook `{}` of `{}` asks for {} which is replaced by `{}`",
+                hook,
+                app,
+                Target(param, *instance),
+                stand_in
+            ),
+            BuildError::DuplicateParam { app, hook, param } => write!(
+                f,
+                "hook `{}` of `{}` asks for `{}` more than once while borrowing it mutably",
+                hook, app, param
+            ),
+            BuildError::Blocked { app, step } => write!(
+                f,
+                "`{}` of `{}` has to wait, the reactor should be built with `build_async`",
+                step, app
+            ),
+            BuildError::BlockedHook { app, hook } => write!(
+                f,
+                "async hook `{}` of `{}` can't run, the reactor should be built with `build_async`",
+                hook, app
+            ),
+            BuildError::HookFailed { app, hook, message } => {
+                write!(f, "hook `{}` of `{}` failed: {}", hook, app, message)
+            }
+            BuildError::UnmountedMiddleware { app } => write!(
+                f,
+                "`{}` has scoped middleware but no mount point to scope them to",
+                app
+            ),
+            BuildError::UnsharedTask { app } => write!(
+                f,
+                "`{}` registers a background task, which only the shared reactors can run",
+                app
+            ),
+            BuildError::UnlabeledMigrations { app } => {
+                write!(f, "`{}` has migrations but no migrations namespace", app)
+            }
+            BuildError::DuplicateNamespace { namespace, apps } => write!(
+                f,
+                "both `{}` and `{}` track their migrations under `{}`",
+                apps.0, apps.1, namespace
+            ),
+        }
+    }
+}
+
+/// An app, or one of its named instances
+struct Target(&'static str, Option<&'static str>);
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(instance) => write!(f, "the `{}` instance of `{}`", instance, self.0),
+            None => write!(f, "`{}`", self.0),
+        }
+    }
+}
+
+/// All the problems found while building a reactor
+#[derive(Debug)]
+pub struct ReactorError {
+    errors: Vec<BuildError>,
+}
+
+impl ReactorError {
+    pub(crate) fn new(errors: Vec<BuildError>) -> Self {
+        Self { errors }
+    }
+
+    pub fn errors(&self) -> &[BuildError] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for ReactorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Failed to build the reactor:")?;
+        for err in self.errors.iter() {
+            write!(f, "\n  - {}", err)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ReactorError {}
+
+/// The reasons a frozen reactor fails to shut down, see
+/// [`FrozenReactor::shutdown`](crate::FrozenReactor::shutdown)
+#[derive(Debug)]
+pub enum ShutdownError {
+    /// The reactor is still shared when the time is up, like when the server factory captures it
+    /// instead of a [`WeakReactor`](crate::WeakReactor)
+    Shared,
+    /// Apps were still finishing when the time was up, the other apps are finished anyway
+    TimedOut { apps: Vec<&'static str> },
+}
+
+impl fmt::Display for ShutdownError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShutdownError::Shared => f.write_str(
+                "the reactor is still shared, the server factory should capture a `WeakReactor`",
+            ),
+            ShutdownError::TimedOut { apps } => {
+                write!(f, "`{}` timed out in `finish`", apps.join("`, `"))
+            }
+        }
+    }
+}
+
+impl Error for ShutdownError {}
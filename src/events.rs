@@ -0,0 +1,128 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use actix_web::dev::Payload;
+use actix_web::error::{Error, ErrorInternalServerError};
+use actix_web::{FromRequest, HttpRequest};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+
+/// An event apps can publish and subscribe to, see [`Registry::subscribe`](crate::Registry::subscribe)
+pub trait Event: Send + Sync + 'static {}
+
+type AnyEvent = dyn Any + Send + Sync;
+
+enum Handler {
+    Sync(Box<dyn Fn(&AnyEvent) + Send + Sync>),
+    Async(Box<dyn Fn(Arc<AnyEvent>) -> LocalBoxFuture<'static, ()> + Send + Sync>),
+}
+
+struct Subscriber {
+    priority: i32,
+    handler: Handler,
+}
+
+/// The subscribers of each event type, ordered by their priority
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    events: HashMap<TypeId, Vec<Subscriber>>,
+}
+
+impl Subscribers {
+    pub(crate) fn subscribe<E, F>(&mut self, priority: i32, handler: F)
+    where
+        E: Event,
+        F: Fn(&E) + Send + Sync + 'static,
+    {
+        let handler = move |event: &AnyEvent| {
+            if let Some(event) = event.downcast_ref() {
+                handler(event)
+            }
+        };
+        self.insert::<E>(priority, Handler::Sync(Box::new(handler)));
+    }
+
+    pub(crate) fn subscribe_async<E, F, Fut>(&mut self, priority: i32, handler: F)
+    where
+        E: Event,
+        F: Fn(Arc<E>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let handler = move |event: Arc<AnyEvent>| -> LocalBoxFuture<'static, ()> {
+            match event.downcast() {
+                Ok(event) => Box::pin(handler(event)),
+                Err(_) => Box::pin(ready(())),
+            }
+        };
+        self.insert::<E>(priority, Handler::Async(Box::new(handler)));
+    }
+
+    fn insert<E>(&mut self, priority: i32, handler: Handler)
+    where
+        E: Event,
+    {
+        let subscribers = self.events.entry(TypeId::of::<E>()).or_default();
+        // after the subscribers with the same priority, so they keep the subscription order
+        let idx = subscribers
+            .iter()
+            .position(|sub| sub.priority < priority)
+            .unwrap_or(subscribers.len());
+        subscribers.insert(idx, Subscriber { priority, handler });
+    }
+}
+
+/// The event bus of a built reactor, cloning it is cheap
+///
+/// It can be extracted in the request handlers of the apps to publish events:
+///
+/// ```ignore
+/// async fn login(events: Events) -> HttpResponse {
+///     events.publish(LoggedIn { username }).await;
+///     // ...
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Events {
+    subscribers: Arc<Subscribers>,
+}
+
+impl Events {
+    pub(crate) fn new(subscribers: Subscribers) -> Self {
+        Self {
+            subscribers: Arc::new(subscribers),
+        }
+    }
+
+    /// Calls the subscribers of the event from the highest priority to the lowest, async
+    /// subscribers are awaited before calling the next one.
+    pub async fn publish<E>(&self, event: E)
+    where
+        E: Event,
+    {
+        let subscribers = match self.subscribers.events.get(&TypeId::of::<E>()) {
+            Some(subscribers) => subscribers,
+            None => return,
+        };
+
+        let event: Arc<AnyEvent> = Arc::new(event);
+        for subscriber in subscribers.iter() {
+            match &subscriber.handler {
+                Handler::Sync(handler) => handler(&*event),
+                Handler::Async(handler) => handler(event.clone()).await,
+            }
+        }
+    }
+}
+
+impl FromRequest for Events {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(req.app_data::<Events>().cloned().ok_or_else(|| {
+            ErrorInternalServerError("The events are not configured, see `configure_app`")
+        }))
+    }
+}
@@ -0,0 +1,105 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use actix_web::dev::Payload;
+use actix_web::error::{Error, ErrorInternalServerError};
+use actix_web::{FromRequest, HttpRequest};
+use futures_util::future::{ready, Ready};
+
+use crate::app::{App, AppId};
+use crate::metrics::ServedBy;
+use crate::reactor::FrozenReactor;
+
+/// Shared access to the built instance of an app from a request handler
+///
+/// The reactor has to be frozen before configuring the actix app, see [`Reactor::freeze`]:
+///
+/// ```ignore
+/// async fn login(auth: AppRef<AuthApp>) -> HttpResponse {
+///     // ...
+/// }
+/// ```
+///
+/// The handlers of an app mounted under a prefix get the instance serving the request, so the
+/// named instances mounted at their own paths get themselves, the other handlers get the unnamed
+/// instance.
+///
+/// [`Reactor::freeze`]: crate::Reactor::freeze
+pub struct AppRef<T> {
+    reactor: FrozenReactor,
+    idx: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Deref for AppRef<T>
+where
+    T: App,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self.reactor.at(self.idx) {
+            Some(app) => app,
+            // the apps of a frozen reactor never change, the index was resolved for `T`
+            None => unreachable!(),
+        }
+    }
+}
+
+impl<T> Clone for AppRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            reactor: self.reactor.clone(),
+            idx: self.idx,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> FromRequest for AppRef<T>
+where
+    T: App,
+{
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let reactor = match req.app_data::<FrozenReactor>() {
+            Some(reactor) => reactor,
+            None => return ready(Err(not_frozen())),
+        };
+
+        // the scopes of the apps are tagged with the instance serving them
+        let id = req
+            .app_data::<ServedBy>()
+            .map(|ServedBy(app)| *app)
+            .filter(|app| *app == AppId::instance_of::<T>(app.instance()))
+            .unwrap_or_else(AppId::of::<T>);
+        let idx = match reactor.position::<T>(id) {
+            Some(idx) => idx,
+            None => {
+                let message = match reactor.stand_in(id) {
+                    Some(stand_in) => {
+                        format!("`{}` is replaced by `{}`", id.name(), stand_in.name())
+                    }
+                    None => format!("`{:?}` is not registered in the reactor", id),
+                };
+                return ready(Err(ErrorInternalServerError(message)));
+            }
+        };
+
+        ready(Ok(Self {
+            reactor: reactor.clone(),
+            idx,
+            _marker: PhantomData,
+        }))
+    }
+}
+
+/// The error of the handlers which need the apps while the reactor is not frozen
+pub(crate) fn not_frozen() -> Error {
+    ErrorInternalServerError(
+        "The apps are not available, the reactor should be frozen before `configure_app`",
+    )
+}
@@ -0,0 +1,128 @@
+use serde::Serialize;
+
+pub(crate) struct Sorted {
+    pub(crate) order: Vec<usize>,
+    // each cycle starts and ends with the same node
+    pub(crate) cycles: Vec<Vec<usize>>,
+}
+
+/// Sorts the nodes of a graph so every node comes after its dependencies.
+///
+/// `deps[i]` holds the indices of the nodes `i` depends on. Ties are broken by the position of the
+/// nodes, so a graph without any edges keeps its original order. Cycles are reported, and broken by
+/// emitting the first node of the cycle.
+pub(crate) fn sort(deps: &[Vec<usize>]) -> Sorted {
+    let mut done = vec![false; deps.len()];
+    let mut sorted = Sorted {
+        order: Vec::with_capacity(deps.len()),
+        cycles: Vec::new(),
+    };
+
+    let pending = |done: &[bool], i: usize| deps[i].iter().find(|&&dep| dep != i && !done[dep]);
+
+    while sorted.order.len() < deps.len() {
+        let ready = (0..deps.len()).find(|&i| !done[i] && pending(&done, i).is_none());
+
+        let next = match ready {
+            Some(next) => next,
+            None => {
+                // Every remaining node waits on another remaining node, so walking the pending
+                // dependencies has to end up in a cycle
+                let mut path = vec![(0..deps.len()).find(|&i| !done[i]).unwrap()];
+                let start = loop {
+                    let dep = *pending(&done, *path.last().unwrap()).unwrap();
+                    if let Some(start) = path.iter().position(|&node| node == dep) {
+                        break start;
+                    }
+                    path.push(dep);
+                };
+
+                let mut cycle = path.split_off(start);
+                let first = *cycle.iter().min().unwrap();
+                cycle.push(cycle[0]);
+                sorted.cycles.push(cycle);
+                first
+            }
+        };
+
+        done[next] = true;
+        sorted.order.push(next);
+    }
+
+    sorted
+}
+
+/// An app in a [`DependencyGraph`]
+#[derive(Clone, Debug, Serialize)]
+pub struct GraphNode {
+    /// The type name of the app
+    pub name: &'static str,
+    /// The name of the instance for the named apps
+    pub instance: Option<&'static str>,
+}
+
+/// An app depending on another one in a [`DependencyGraph`]
+#[derive(Clone, Debug, Serialize)]
+pub struct GraphEdge {
+    /// The index of the dependent app
+    pub from: usize,
+    /// The index of the app it depends on
+    pub to: usize,
+    /// The DI hook asking for the app, `None` for the dependencies declared by the app
+    pub hook: Option<String>,
+    /// Whether the hook borrows the app mutably
+    pub mutable: bool,
+    /// Whether the hook can run without the app
+    pub optional: bool,
+}
+
+/// The apps of a reactor and their dependencies, see [`Reactor::graph`](crate::Reactor::graph)
+#[derive(Clone, Debug, Serialize)]
+pub struct DependencyGraph {
+    pub apps: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl DependencyGraph {
+    /// Renders the graph in the DOT format of graphviz, the edges of the hooks are labeled by the
+    /// name of the hook, bold when the hook borrows the app mutably, and dashed when the hook can
+    /// run without the app.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph apps {\n");
+        for (idx, app) in self.apps.iter().enumerate() {
+            let label = match app.instance {
+                Some(instance) => format!("{} ({})", app.name, instance),
+                None => app.name.to_string(),
+            };
+            dot.push_str(&format!("    {} [label=\"{}\"];\n", idx, escape(&label)));
+        }
+        for edge in self.edges.iter() {
+            let mut attrs = Vec::new();
+            if let Some(hook) = &edge.hook {
+                attrs.push(format!("label=\"{}\"", escape(hook)));
+            }
+            match (edge.mutable, edge.optional) {
+                (true, true) => attrs.push("style=\"bold,dashed\"".to_string()),
+                (true, false) => attrs.push("style=bold".to_string()),
+                (false, true) => attrs.push("style=dashed".to_string()),
+                (false, false) => {}
+            }
+            dot.push_str(&format!("    {} -> {}", edge.from, edge.to));
+            if !attrs.is_empty() {
+                dot.push_str(&format!(" [{}]", attrs.join(", ")));
+            }
+            dot.push_str(";\n");
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Serializes the graph to JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("The graph is always serializable")
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use actix_rt::time::timeout;
+use actix_web::error::Error;
+use actix_web::http::StatusCode;
+use actix_web::web::{self, ServiceConfig};
+use actix_web::HttpRequest;
+use futures_util::future::join_all;
+use serde::Serialize;
+use yeax_api::JsonResponse;
+
+use crate::app::App;
+use crate::extract::not_frozen;
+use crate::reactor::{Entry, FrozenReactor};
+
+/// The health of an app, see [`App::health`](crate::App::health)
+#[derive(Debug, Serialize)]
+pub struct AppHealth {
+    pub app: &'static str,
+    pub instance: Option<&'static str>,
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The health of all the apps with a health check
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// Whether all the checks passed
+    pub healthy: bool,
+    pub apps: Vec<AppHealth>,
+}
+
+/// Runs the health checks of the apps concurrently, a check taking longer than `limit` fails
+pub(crate) async fn check<'a, I>(entries: I, limit: Duration) -> HealthReport
+where
+    I: Iterator<Item = &'a Entry<dyn App + Send + Sync>>,
+{
+    let checks = entries.filter_map(|entry| {
+        let check = entry.app.health()?;
+        let id = entry.own_id();
+        Some(async move {
+            let error = match timeout(limit, check).await {
+                Ok(Ok(())) => None,
+                Ok(Err(message)) => Some(message),
+                Err(_) => Some(format!("timed out after {:?}", limit)),
+            };
+            AppHealth {
+                app: id.name(),
+                instance: id.instance(),
+                healthy: error.is_none(),
+                error,
+            }
+        })
+    });
+
+    let apps = join_all(checks).await;
+    HealthReport {
+        healthy: apps.iter().all(|app| app.healthy),
+        apps,
+    }
+}
+
+/// The route serving the health report of the apps
+#[derive(Clone)]
+pub(crate) struct HealthEndpoint {
+    pub(crate) path: String,
+    pub(crate) limit: Duration,
+}
+
+impl HealthEndpoint {
+    pub(crate) fn configure(&self, cfg: &mut ServiceConfig) {
+        let limit = self.limit;
+        cfg.route(
+            &self.path,
+            web::get().to(move |req: HttpRequest| report(req, limit)),
+        );
+    }
+}
+
+async fn report(req: HttpRequest, limit: Duration) -> Result<JsonResponse<HealthReport>, Error> {
+    let reactor = req.app_data::<FrozenReactor>().ok_or_else(not_frozen)?;
+    let report = reactor.health(limit).await;
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(JsonResponse::with_content(report).status(status))
+}
@@ -1,6 +1,35 @@
 mod app;
+mod cli;
+mod config;
 mod di;
+mod error;
+mod events;
+mod extract;
+mod graph;
+mod health;
+mod metrics;
+mod middleware;
+mod migrate;
 mod reactor;
+mod tasks;
+pub mod test;
+mod trace;
 
-pub use app::App;
-pub use reactor::{ActixReactorExt, Reactor, Registry};
+pub use app::{App, AppId, AppObject, IntoAppObject};
+pub use cli::{Args, CliError, Command, CommandHandler, Commands};
+pub use config::{ConfigError, Configurable, Settings};
+pub use di::{Dependency, InstanceName, Loan, Named, Param};
+pub use error::{BuildError, ReactorError, ShutdownError};
+pub use events::{Event, Events};
+pub use extract::AppRef;
+pub use graph::{DependencyGraph, GraphEdge, GraphNode};
+pub use health::{AppHealth, HealthReport};
+pub use metrics::Counter;
+pub use middleware::{Middleware, MiddlewareConfig, Next, ReactorMiddleware};
+pub use migrate::{MigrateError, Migration, MigrationExecutor, MigrationStatus, Migrator};
+pub use reactor::{
+    ActixMiddlewareExt, ActixReactorExt, ConfigureApps, FrozenReactor, Reactor, Registry,
+    SharedReactor, WeakReactor,
+};
+pub use tasks::Schedule;
+pub use yeax_derive::Inject;
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::Error;
+use actix_web::web::{self, ServiceConfig};
+use actix_web::{HttpRequest, HttpResponse};
+use futures_util::future::LocalBoxFuture;
+
+use crate::app::AppId;
+use crate::extract::not_frozen;
+use crate::middleware::{Middleware, Next};
+use crate::reactor::FrozenReactor;
+
+/// The upper bounds of the request duration buckets, in seconds
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A counter registered by an app, see [`Registry::counter`](crate::Registry::counter)
+///
+/// Cloning it is cheap, the clones share the same value.
+#[derive(Clone, Debug, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1)
+    }
+
+    pub fn add(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A counter along with the app which registered it
+pub(crate) struct AppCounter {
+    pub(crate) app: &'static str,
+    pub(crate) instance: Option<&'static str>,
+    pub(crate) name: &'static str,
+    pub(crate) help: &'static str,
+    pub(crate) counter: Counter,
+}
+
+/// The app serving the requests routed to a scope, set as the data of the scope
+pub(crate) struct ServedBy(pub(crate) AppId);
+
+// the app, its instance and the route pattern
+type RouteKey = (&'static str, Option<&'static str>, String);
+
+#[derive(Default)]
+struct Requests {
+    // by the class of the status code, 2 for 2xx
+    statuses: BTreeMap<u16, u64>,
+    // not cumulative, the last one is for the requests slower than all the bounds
+    buckets: [u64; BUCKETS.len() + 1],
+    count: u64,
+    seconds: f64,
+}
+
+#[derive(Default)]
+struct Store {
+    requests: BTreeMap<RouteKey, Requests>,
+    counters: Vec<AppCounter>,
+}
+
+/// The request metrics and the counters of the apps
+#[derive(Clone, Default)]
+pub(crate) struct Metrics {
+    // the path of the endpoint, requests are recorded only when it's set
+    path: Option<String>,
+    store: Arc<Mutex<Store>>,
+}
+
+impl Metrics {
+    pub(crate) fn serve_at(&mut self, path: &str) {
+        self.path = Some(path.to_string());
+    }
+
+    pub(crate) fn add_counters(&self, counters: Vec<AppCounter>) {
+        self.store.lock().unwrap().counters.extend(counters);
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// The middleware recording the requests, `None` if the endpoint is not enabled
+    pub(crate) fn recorder(&self) -> Option<Arc<dyn Middleware>> {
+        self.path.as_ref()?;
+        Some(Arc::new(Recorder(self.store.clone())))
+    }
+
+    pub(crate) fn configure(&self, cfg: &mut ServiceConfig) {
+        if let Some(path) = &self.path {
+            cfg.route(path, web::get().to(report));
+        }
+    }
+
+    /// Renders the metrics in the Prometheus text format
+    pub(crate) fn render(&self) -> String {
+        let store = self.store.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP yeax_requests_total The requests handled by the apps\n");
+        out.push_str("# TYPE yeax_requests_total counter\n");
+        for ((app, instance, route), requests) in store.requests.iter() {
+            let labels = route_labels(app, *instance, route);
+            for (class, count) in requests.statuses.iter() {
+                let _ = writeln!(
+                    out,
+                    "yeax_requests_total{{{},status=\"{}xx\"}} {}",
+                    labels, class, count
+                );
+            }
+        }
+
+        out.push_str(
+            "# HELP yeax_request_duration_seconds The time taken to handle the requests\n",
+        );
+        out.push_str("# TYPE yeax_request_duration_seconds histogram\n");
+        for ((app, instance, route), requests) in store.requests.iter() {
+            let labels = route_labels(app, *instance, route);
+            let mut cumulative = 0;
+            for (bound, count) in BUCKETS.iter().zip(requests.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "yeax_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "yeax_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, requests.count
+            );
+            let _ = writeln!(
+                out,
+                "yeax_request_duration_seconds_sum{{{}}} {}",
+                labels, requests.seconds
+            );
+            let _ = writeln!(
+                out,
+                "yeax_request_duration_seconds_count{{{}}} {}",
+                labels, requests.count
+            );
+        }
+
+        // the counters of the same name are reported together, with the help of the first one
+        let mut counters: Vec<_> = store.counters.iter().collect();
+        counters.sort_by_key(|counter| counter.name);
+        let mut previous = None;
+        for counter in counters {
+            if previous != Some(counter.name) {
+                let _ = writeln!(out, "# HELP {} {}", counter.name, counter.help);
+                let _ = writeln!(out, "# TYPE {} counter", counter.name);
+                previous = Some(counter.name);
+            }
+            let _ = writeln!(
+                out,
+                "{}{{{}}} {}",
+                counter.name,
+                app_labels(counter.app, counter.instance),
+                counter.counter.get()
+            );
+        }
+        out
+    }
+}
+
+/// Records the requests under the app and the route which served them
+struct Recorder(Arc<Mutex<Store>>);
+
+impl Middleware for Recorder {
+    fn call(
+        &self,
+        req: ServiceRequest,
+        next: Next,
+    ) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>> {
+        let store = self.0.clone();
+        let start = Instant::now();
+        Box::pin(async move {
+            let res = next.call(req).await;
+            let seconds = start.elapsed().as_secs_f64();
+
+            // the request is only available along with a response, errors are unattributed
+            let (key, status) = match &res {
+                Ok(res) => {
+                    let req = res.request();
+                    // the unmatched requests end up in the innermost scope of the root apps
+                    let key = match (req.match_pattern(), req.app_data::<ServedBy>()) {
+                        (Some(route), Some(ServedBy(app))) => (app.name(), app.instance(), route),
+                        (route, _) => ("", None, route.unwrap_or_default()),
+                    };
+                    (key, res.status().as_u16())
+                }
+                Err(err) => (
+                    ("", None, String::new()),
+                    err.as_response_error().status_code().as_u16(),
+                ),
+            };
+
+            let mut store = store.lock().unwrap();
+            let requests = store.requests.entry(key).or_default();
+            *requests.statuses.entry(status / 100).or_default() += 1;
+            let bucket = BUCKETS
+                .iter()
+                .position(|bound| seconds <= *bound)
+                .unwrap_or(BUCKETS.len());
+            requests.buckets[bucket] += 1;
+            requests.count += 1;
+            requests.seconds += seconds;
+            drop(store);
+            res
+        })
+    }
+}
+
+async fn report(req: HttpRequest) -> Result<HttpResponse, Error> {
+    let reactor = req.app_data::<FrozenReactor>().ok_or_else(not_frozen)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(reactor.metrics()))
+}
+
+fn app_labels(app: &str, instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("app=\"{}\",instance=\"{}\"", escape(app), escape(instance)),
+        None => format!("app=\"{}\"", escape(app)),
+    }
+}
+
+fn route_labels(app: &str, instance: Option<&str>, route: &str) -> String {
+    format!("{},route=\"{}\"", app_labels(app, instance), escape(route))
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
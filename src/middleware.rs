@@ -0,0 +1,176 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use actix_service::{Service, Transform};
+use actix_web::dev::{Body, MessageBody, ResponseBody, ServiceRequest, ServiceResponse};
+use actix_web::error::Error;
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+
+/// A middleware contributed by an app, see [`App::configure_middleware`](crate::App::configure_middleware)
+///
+/// It's implemented for the async closures taking the request and the rest of the chain:
+///
+/// ```ignore
+/// cfg.wrap(|req: ServiceRequest, next: Next| async move {
+///     let res = next.call(req).await?;
+///     // ...
+///     Ok(res)
+/// });
+/// ```
+pub trait Middleware: Send + Sync + 'static {
+    fn call(
+        &self,
+        req: ServiceRequest,
+        next: Next,
+    ) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>>;
+}
+
+impl<F, Fut> Middleware for F
+where
+    F: Fn(ServiceRequest, Next) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<ServiceResponse, Error>> + 'static,
+{
+    fn call(
+        &self,
+        req: ServiceRequest,
+        next: Next,
+    ) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>> {
+        Box::pin(self(req, next))
+    }
+}
+
+type Endpoint =
+    Rc<dyn Fn(ServiceRequest) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>>>;
+
+/// The rest of a middleware chain, ending with the wrapped service
+pub struct Next {
+    middleware: Arc<Vec<Arc<dyn Middleware>>>,
+    idx: usize,
+    endpoint: Endpoint,
+}
+
+impl Next {
+    /// Passes the request to the next middleware, or to the wrapped service at the end of the
+    /// chain
+    pub fn call(
+        self,
+        req: ServiceRequest,
+    ) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>> {
+        match self.middleware.get(self.idx).cloned() {
+            Some(middleware) => middleware.call(
+                req,
+                Next {
+                    idx: self.idx + 1,
+                    ..self
+                },
+            ),
+            None => (self.endpoint)(req),
+        }
+    }
+}
+
+/// The middleware the apps contribute, filled by [`App::configure_middleware`](crate::App::configure_middleware)
+#[derive(Default)]
+pub struct MiddlewareConfig {
+    pub(crate) global: Vec<Arc<dyn Middleware>>,
+    pub(crate) scoped: Vec<Arc<dyn Middleware>>,
+}
+
+impl MiddlewareConfig {
+    /// Wraps the whole application, see [`Reactor::middleware`](crate::Reactor::middleware)
+    pub fn wrap<M>(&mut self, middleware: M) -> &mut Self
+    where
+        M: Middleware,
+    {
+        self.global.push(Arc::new(middleware));
+        self
+    }
+
+    /// Wraps only the services of the app, under its mount point
+    pub fn wrap_scoped<M>(&mut self, middleware: M) -> &mut Self
+    where
+        M: Middleware,
+    {
+        self.scoped.push(Arc::new(middleware));
+        self
+    }
+}
+
+/// An actix middleware running a chain of the middleware contributed by the apps, in the order
+/// they were added
+#[derive(Clone)]
+pub struct ReactorMiddleware {
+    middleware: Arc<Vec<Arc<dyn Middleware>>>,
+}
+
+impl ReactorMiddleware {
+    pub(crate) fn new(middleware: Vec<Arc<dyn Middleware>>) -> Self {
+        Self {
+            middleware: Arc::new(middleware),
+        }
+    }
+}
+
+impl<S, B> Transform<S> for ReactorMiddleware
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + Unpin + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ReactorMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, ()>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let service = Rc::new(RefCell::new(service));
+        let inner = service.clone();
+        let endpoint: Endpoint = Rc::new(move |req| {
+            let fut = inner.borrow_mut().call(req);
+            Box::pin(async move {
+                let res = fut.await?;
+                Ok(res.map_body(|_, body| ResponseBody::Other(Body::from_message(body))))
+            })
+        });
+
+        ready(Ok(ReactorMiddlewareService {
+            service,
+            middleware: self.middleware.clone(),
+            endpoint,
+        }))
+    }
+}
+
+pub struct ReactorMiddlewareService<S> {
+    service: Rc<RefCell<S>>,
+    middleware: Arc<Vec<Arc<dyn Middleware>>>,
+    endpoint: Endpoint,
+}
+
+impl<S, B> Service for ReactorMiddlewareService<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<ServiceResponse, Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let next = Next {
+            middleware: self.middleware.clone(),
+            idx: 0,
+            endpoint: self.endpoint.clone(),
+        };
+        next.call(req)
+    }
+}
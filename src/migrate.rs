@@ -0,0 +1,314 @@
+use std::error::Error;
+use std::fmt;
+
+use futures_util::future::LocalBoxFuture;
+
+/// A schema change shipped by an app, see [`App::migrations`](crate::App::migrations)
+///
+/// ```ignore
+/// Migration::new("0001_users", "CREATE TABLE users (name TEXT NOT NULL)").down("DROP TABLE users")
+/// ```
+#[derive(Clone, Debug)]
+pub struct Migration {
+    name: &'static str,
+    up: &'static str,
+    down: Option<&'static str>,
+}
+
+impl Migration {
+    /// A migration which can't be rolled back, the name has to be unique in the app
+    pub fn new(name: &'static str, up: &'static str) -> Self {
+        Self {
+            name,
+            up,
+            down: None,
+        }
+    }
+
+    /// The SQL undoing the migration, needed to roll it back
+    pub fn down(mut self, sql: &'static str) -> Self {
+        self.down = Some(sql);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// Runs the migrations against a database and keeps track of the applied ones
+///
+/// With the `sqlx` feature it's implemented for `sqlx::AnyPool`.
+pub trait MigrationExecutor {
+    /// Creates the bookkeeping table if it doesn't exist, and lists the `(app, name)` of the
+    /// applied migrations in the order they were applied, `app` being the migrations namespace of
+    /// the app
+    fn applied(&mut self) -> LocalBoxFuture<'_, Result<Vec<(String, String)>, String>>;
+
+    /// Runs the SQL of a migration and records it as applied after the others, atomically if the
+    /// database allows
+    fn apply<'a>(
+        &'a mut self,
+        app: &'a str,
+        name: &'a str,
+        sql: &'a str,
+    ) -> LocalBoxFuture<'a, Result<(), String>>;
+
+    /// Runs the SQL undoing a migration and removes its record, atomically if the database allows
+    fn revert<'a>(
+        &'a mut self,
+        app: &'a str,
+        name: &'a str,
+        sql: &'a str,
+    ) -> LocalBoxFuture<'a, Result<(), String>>;
+}
+
+/// A migration of an app and whether it's applied
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// The migrations namespace of the app
+    pub app: &'static str,
+    pub name: &'static str,
+    pub applied: bool,
+}
+
+/// The migrations of all the apps, in the order they are applied
+///
+/// Created by [`Reactor::migrator`](crate::Reactor::migrator).
+pub struct Migrator {
+    // the migrations of each namespace, apps come after the apps they depend on
+    apps: Vec<(&'static str, Vec<Migration>)>,
+}
+
+impl Migrator {
+    pub(crate) fn new(apps: Vec<(&'static str, Vec<Migration>)>) -> Self {
+        Self { apps }
+    }
+
+    fn plan(&self) -> impl Iterator<Item = (&'static str, &Migration)> {
+        self.apps
+            .iter()
+            .flat_map(|(app, migrations)| migrations.iter().map(move |migration| (*app, migration)))
+    }
+
+    /// Lists all the migrations in the order they are applied
+    pub async fn status<E>(&self, executor: &mut E) -> Result<Vec<MigrationStatus>, MigrateError>
+    where
+        E: MigrationExecutor,
+    {
+        let applied = executor.applied().await.map_err(MigrateError::Database)?;
+        let status = self
+            .plan()
+            .map(|(app, migration)| MigrationStatus {
+                app,
+                name: migration.name,
+                applied: applied
+                    .iter()
+                    .any(|(applied_app, name)| applied_app == app && name == migration.name),
+            })
+            .collect();
+        Ok(status)
+    }
+
+    /// Applies the pending migrations, the migrations of the apps are applied after the ones of
+    /// the apps they depend on, and in the order they are declared for the same app.
+    ///
+    /// Returns the applied migrations, stopping at the first failure.
+    pub async fn migrate<E>(&self, executor: &mut E) -> Result<Vec<MigrationStatus>, MigrateError>
+    where
+        E: MigrationExecutor,
+    {
+        let pending: Vec<_> = self
+            .status(executor)
+            .await?
+            .into_iter()
+            .zip(self.plan())
+            .filter(|(status, _)| !status.applied)
+            .collect();
+
+        let mut applied = Vec::with_capacity(pending.len());
+        for (mut status, (app, migration)) in pending {
+            executor
+                .apply(app, migration.name, migration.up)
+                .await
+                .map_err(|message| MigrateError::Failed {
+                    app,
+                    migration: migration.name,
+                    message,
+                })?;
+            status.applied = true;
+            applied.push(status);
+        }
+        Ok(applied)
+    }
+
+    /// Rolls back the last `steps` applied migrations, in the reverse order they were applied.
+    ///
+    /// Nothing is rolled back if one of them has no `down` SQL. Each migration is rolled back
+    /// atomically if the database allows, and the rollback stops at the first failure, leaving the
+    /// failed migration applied and the ones before it rolled back. Returns the rolled back
+    /// migrations.
+    pub async fn rollback<E>(
+        &self,
+        executor: &mut E,
+        steps: usize,
+    ) -> Result<Vec<MigrationStatus>, MigrateError>
+    where
+        E: MigrationExecutor,
+    {
+        let applied = executor.applied().await.map_err(MigrateError::Database)?;
+        // the migrations of the apps which are not in the reactor are left alone
+        let targets: Vec<_> = applied
+            .iter()
+            .rev()
+            .filter_map(|(applied_app, name)| {
+                self.plan()
+                    .find(|(app, migration)| app == applied_app && migration.name == name)
+            })
+            .take(steps)
+            .collect();
+
+        if let Some((app, migration)) = targets.iter().find(|(_, m)| m.down.is_none()) {
+            return Err(MigrateError::Irreversible {
+                app,
+                migration: migration.name,
+            });
+        }
+
+        let mut reverted = Vec::with_capacity(targets.len());
+        for (app, migration) in targets {
+            let down = migration.down.unwrap_or_default();
+            executor
+                .revert(app, migration.name, down)
+                .await
+                .map_err(|message| MigrateError::Failed {
+                    app,
+                    migration: migration.name,
+                    message,
+                })?;
+            reverted.push(MigrationStatus {
+                app,
+                name: migration.name,
+                applied: false,
+            });
+        }
+        Ok(reverted)
+    }
+}
+
+/// The reasons migrating the apps fails
+#[derive(Debug)]
+pub enum MigrateError {
+    /// The bookkeeping table couldn't be read
+    Database(String),
+    /// A migration failed to apply or to roll back
+    Failed {
+        app: &'static str,
+        migration: &'static str,
+        message: String,
+    },
+    /// A migration to roll back has no `down` SQL
+    Irreversible {
+        app: &'static str,
+        migration: &'static str,
+    },
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrateError::Database(message) => {
+                write!(f, "failed to read the applied migrations: {}", message)
+            }
+            MigrateError::Failed {
+                app,
+                migration,
+                message,
+            } => write!(
+                f,
+                "migration `{}` of `{}` failed: {}",
+                migration, app, message
+            ),
+            MigrateError::Irreversible { app, migration } => write!(
+                f,
+                "migration `{}` of `{}` can't be rolled back",
+                migration, app
+            ),
+        }
+    }
+}
+
+impl Error for MigrateError {}
+
+#[cfg(feature = "sqlx")]
+mod sqlx_executor {
+    use futures_util::future::LocalBoxFuture;
+    use sqlx::{AnyPool, Executor};
+
+    use super::MigrationExecutor;
+
+    // `seq` is the order the migrations were applied in, which the rollback follows
+    const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS yeax_migrations (
+        app VARCHAR(255) NOT NULL,
+        name VARCHAR(255) NOT NULL,
+        seq BIGINT NOT NULL,
+        PRIMARY KEY (app, name)
+    )";
+
+    // the placeholders differ between the databases, the values are app and migration names
+    // declared in the code so they are quoted instead
+    fn quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    async fn run(pool: &AnyPool, sql: &str, bookkeeping: String) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        (&mut tx).execute(sql).await?;
+        (&mut tx).execute(bookkeeping.as_str()).await?;
+        tx.commit().await
+    }
+
+    impl MigrationExecutor for AnyPool {
+        fn applied(&mut self) -> LocalBoxFuture<'_, Result<Vec<(String, String)>, String>> {
+            Box::pin(async move {
+                (&*self)
+                    .execute(CREATE_TABLE)
+                    .await
+                    .map_err(|err| err.to_string())?;
+                sqlx::query_as("SELECT app, name FROM yeax_migrations ORDER BY seq")
+                    .fetch_all(&*self)
+                    .await
+                    .map_err(|err| err.to_string())
+            })
+        }
+
+        fn apply<'a>(
+            &'a mut self,
+            app: &'a str,
+            name: &'a str,
+            sql: &'a str,
+        ) -> LocalBoxFuture<'a, Result<(), String>> {
+            let record = format!(
+                "INSERT INTO yeax_migrations (app, name, seq) \
+                 SELECT {}, {}, COALESCE(MAX(seq), 0) + 1 FROM yeax_migrations",
+                quote(app),
+                quote(name)
+            );
+            Box::pin(async move { run(self, sql, record).await.map_err(|err| err.to_string()) })
+        }
+
+        fn revert<'a>(
+            &'a mut self,
+            app: &'a str,
+            name: &'a str,
+            sql: &'a str,
+        ) -> LocalBoxFuture<'a, Result<(), String>> {
+            let forget = format!(
+                "DELETE FROM yeax_migrations WHERE app = {} AND name = {}",
+                quote(app),
+                quote(name)
+            );
+            Box::pin(async move { run(self, sql, forget).await.map_err(|err| err.to_string()) })
+        }
+    }
+}
@@ -1,64 +1,1514 @@
-use std::any::TypeId;
-use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::path::Path;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
+use actix_rt::time::timeout;
 use actix_service::ServiceFactory;
-use actix_web::dev::{MessageBody, ServiceRequest, ServiceResponse};
+use actix_web::dev::{Body, MessageBody, Server, Service, ServiceRequest, ServiceResponse};
 use actix_web::error::Error;
+use actix_web::web::{self, ServiceConfig};
 use actix_web::App as ActixApp;
+use futures_channel::mpsc;
+use futures_util::future::FutureExt;
+use futures_util::stream::StreamExt;
 
-use crate::app::App;
-use crate::di::{InjectFactory, Injectable, Injector};
+use crate::app::{App, AppId, AppObject, IntoAppObject};
+use crate::cli::{Cli, CliError, Commands, Invocation};
+use crate::config::{self, Configurable, Loader, Settings};
+use crate::di::{
+    self, AsyncInjectFactory, AsyncInjectable, HookError, InjectFactory, Injectable, Injector,
+    Loan, Param,
+};
+use crate::error::{BuildError, ReactorError, ShutdownError};
+use crate::events::{Event, Events, Subscribers};
+use crate::graph::{self, DependencyGraph, GraphEdge, GraphNode};
+use crate::health::{self, HealthEndpoint, HealthReport};
+use crate::metrics::{AppCounter, Counter, Metrics, ServedBy};
+use crate::middleware::{MiddlewareConfig, ReactorMiddleware};
+use crate::migrate::Migrator;
+use crate::tasks::{self, Schedule, Task, Tasks};
+use crate::trace::{self, Instrument};
 
-#[derive(Default)]
-pub struct Reactor {
-    pub(crate) apps: HashMap<TypeId, Box<dyn App>>,
+pub(crate) struct Entry<A: ?Sized = dyn App> {
+    pub(crate) id: AppId,
+    pub(crate) app: Box<A>,
+    // overrides the mount point declared by the app
+    mount: Option<String>,
+    // set for the apps added with `add_configured`
+    loader: Option<Loader>,
+    // the own identity of a stand-in, registered under the identity of the app it replaces
+    stand_in: Option<AppId>,
+    // collected once the app is initialized
+    middleware: MiddlewareConfig,
+}
+
+impl<A> Entry<A>
+where
+    A: ?Sized + AppObject,
+{
+    /// The identity of the app the entry actually holds
+    pub(crate) fn own_id(&self) -> AppId {
+        self.stand_in.unwrap_or(self.id)
+    }
+
+    fn matches(&self, id: AppId) -> bool {
+        self.id == id || self.stand_in == Some(id)
+    }
+
+    /// Whether the entry holds a stand-in instead of the app `id`
+    fn is_replaced(&self, id: AppId) -> bool {
+        self.id == id && self.stand_in.is_some()
+    }
+
+    fn prefix(&self) -> &str {
+        self.mount
+            .as_deref()
+            .unwrap_or_else(|| self.app.mount_point())
+    }
+
+    /// Configures the web services of the app in a scope under its mount point, nesting the root
+    /// apps of `rest` in it since an empty scope catches every request
+    fn configure_web(&self, cfg: &mut ServiceConfig, rest: &[&Entry<A>]) {
+        // lets the metrics attribute the requests to the app
+        let scope = web::scope(self.prefix()).app_data(ServedBy(self.own_id()));
+        let configure = |cfg: &mut ServiceConfig| {
+            self.app.configure_web(cfg);
+            configure_roots(rest, cfg);
+        };
+        let middleware: Vec<_> = trace::requests(self.own_id(), self.prefix().is_empty())
+            .into_iter()
+            .chain(self.middleware.scoped.iter().cloned())
+            .collect();
+        if middleware.is_empty() {
+            cfg.service(scope.configure(configure));
+        } else {
+            let middleware = ReactorMiddleware::new(middleware);
+            cfg.service(scope.wrap(middleware).configure(configure));
+        }
+    }
+}
+
+/// The apps of a project, holding them as `A`
+///
+/// Any app can be added to the default reactor, while the reactors created with
+/// [`Reactor::shared`] only accept the apps which are `Send` and `Sync`, and can be frozen to share
+/// the built apps between the workers of an actix server.
+pub struct Reactor<A: ?Sized = dyn App> {
+    pub(crate) apps: Vec<Entry<A>>,
+    // indices into `apps`, insertion order until the reactor is built
+    order: Vec<usize>,
     registry: Registry,
+    settings: Settings,
+    events: Events,
+    // background tasks registered by the apps, started by `FrozenReactor::start_tasks`
+    tasks: Vec<Task>,
+    health: Option<HealthEndpoint>,
+    metrics: Metrics,
+    // apps which failed to be replaced
+    errors: Vec<BuildError>,
+}
+
+/// A reactor whose apps are `Send` and `Sync`, see [`Reactor::shared`]
+pub type SharedReactor = Reactor<dyn App + Send + Sync>;
+
+impl Default for Reactor {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl SharedReactor {
+    /// Creates a reactor which only accepts the apps which are `Send` and `Sync`, so it can be
+    /// frozen once it's built, see [`Reactor::freeze`]
+    pub fn shared() -> Self {
+        Self::empty()
+    }
+
+    /// Serves the health report of the apps at `path`, like `/_health`, for the liveness and
+    /// readiness probes of an orchestrator.
+    ///
+    /// The health checks of the apps are run concurrently on every request, and the ones taking
+    /// longer than `limit` fail. The report is a `yeax_api::JsonResponse` with the status `200`
+    /// when all the checks pass, and `503` otherwise. The checks need the frozen apps, so the
+    /// reactor has to be frozen before configuring the actix app, see [`App::health`].
+    pub fn with_health_endpoint(mut self, path: &str, limit: Duration) -> Self {
+        self.health = Some(HealthEndpoint {
+            path: path.to_string(),
+            limit,
+        });
+        self
+    }
+
+    /// Records the requests handled by the apps and serves their metrics at `path`, like
+    /// `/metrics`, in the Prometheus text format.
+    ///
+    /// The requests are counted by app, route pattern and status code class, along with a
+    /// histogram of their durations, and the counters registered by the apps with
+    /// [`Registry::counter`] are reported too. The requests are attributed to the app serving
+    /// them, the ones no app serves have an empty `app` label.
+    ///
+    /// The requests are recorded by [`ActixMiddlewareExt::wrap_reactor`]. The report needs the
+    /// frozen apps, so the reactor has to be frozen before configuring the actix app.
+    pub fn with_metrics_endpoint(mut self, path: &str) -> Self {
+        self.metrics.serve_at(path);
+        self
+    }
+
+    /// Freezes a built reactor so it can be shared between the workers of an actix server.
+    ///
+    /// The apps are initialized only once, and every worker configures its web app using the same
+    /// instances:
+    ///
+    /// ```ignore
+    /// let reactor = Reactor::shared().add(FirstApp).build().freeze();
+    /// let weak = reactor.weak();
+    /// HttpServer::new(move || App::new().configure_app(&weak))
+    /// ```
+    pub fn freeze(self) -> FrozenReactor {
+        let graph = self.graph();
+        let (released, on_release) = mpsc::unbounded();
+        FrozenReactor::new(Arc::new(Frozen {
+            graph,
+            apps: self.apps,
+            order: self.order,
+            events: self.events,
+            tasks: Mutex::new(Tasks::new(self.tasks)),
+            health: self.health,
+            metrics: self.metrics,
+            released,
+            on_release: Mutex::new(Some(on_release)),
+        }))
+    }
+
+    /// Runs a management command added by the apps, see [`App::configure_commands`].
+    ///
+    /// The first argument is the name of the program, like in [`std::env::args`], and the second
+    /// one the name of the command. The reactor is built and frozen before the commands are
+    /// collected, and the apps are finished once the command is done. Running the program without
+    /// a command, or with `help`, returns the commands grouped by the apps which added them, and
+    /// `help <command>` returns the arguments of a command:
+    ///
+    /// ```ignore
+    /// match reactor.run_cli(std::env::args()).await {
+    ///     Ok(Some(help)) => println!("{}", help),
+    ///     Ok(None) => {}
+    ///     Err(err) => {
+    ///         eprintln!("{}", err);
+    ///         std::process::exit(1);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// The handlers shouldn't keep a clone of the reactor, the apps can't be finished otherwise
+    /// and [`CliError::Unfinished`] is returned along with the result of the command.
+    ///
+    /// Only the shared reactors can run the commands, as the handlers get the frozen reactor.
+    pub async fn run_cli<I, S>(self, args: I) -> Result<Option<String>, CliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        let program = args.next().unwrap_or_default();
+        let args: Vec<String> = args.collect();
+
+        let reactor = self.try_build_async().await.map_err(CliError::Build)?;
+        let cli = reactor.cli(&program);
+        let reactor = reactor.freeze();
+        let mut command = "";
+        let result = match cli {
+            Ok(cli) => match cli.parse(&args) {
+                Ok(Invocation::Help(help)) => Ok(Some(help)),
+                Ok(Invocation::Run(registered, args)) => {
+                    command = registered.name();
+                    Cli::run(registered, reactor.clone(), args)
+                        .await
+                        .map(|()| None)
+                }
+                Err(err) => Err(err),
+            },
+            Err(err) => Err(err),
+        };
+
+        match reactor.unfreeze() {
+            Ok(reactor) => reactor.finish_async().await,
+            Err(_) => {
+                return Err(CliError::Unfinished {
+                    command: command.to_string(),
+                    result: result.map(|_| ()).map_err(Box::new),
+                })
+            }
+        }
+        result
+    }
 }
 
-impl Reactor {
-    pub fn add<T>(mut self, app: T) -> Self
+impl<A> Reactor<A>
+where
+    A: ?Sized + AppObject,
+{
+    fn empty() -> Self {
+        Self {
+            apps: Vec::new(),
+            order: Vec::new(),
+            registry: Registry {
+                shared: A::SHARED,
+                ..Registry::default()
+            },
+            settings: Settings::default(),
+            events: Events::default(),
+            tasks: Vec::new(),
+            health: None,
+            metrics: Metrics::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Registers an app, apps are initialized in the order they are added.
+    ///
+    /// Adding an app of a type that is already registered replaces the old one in place.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<T>(self, app: T) -> Self
     where
-        T: App,
+        T: IntoAppObject<A>,
     {
-        self.apps.insert(TypeId::of::<T>(), Box::new(app));
+        self.insert(AppId::of::<T>(), app, None, None)
+    }
+
+    /// Registers a named instance of an app, many instances of the same app type can be added
+    /// using different names.
+    ///
+    /// DI hooks ask for a named instance using [`Named`](crate::Named), while the unnamed
+    /// parameters keep targeting the instance added with [`Reactor::add`].
+    pub fn add_named<T>(self, name: &'static str, app: T) -> Self
+    where
+        T: IntoAppObject<A>,
+    {
+        self.insert(AppId::named::<T>(name), app, None, None)
+    }
+
+    /// Registers a named instance of an app like [`Reactor::add_named`], mounting its web services
+    /// under `prefix`
+    pub fn add_named_at<T>(self, name: &'static str, prefix: &str, app: T) -> Self
+    where
+        T: IntoAppObject<A>,
+    {
+        let mount = Some(prefix.to_string());
+        self.insert(AppId::named::<T>(name), app, mount, None)
+    }
+
+    /// Registers an app like [`Reactor::add`], mounting its web services under `prefix` instead
+    /// of the mount point declared by the app. An empty prefix mounts them at the root.
+    pub fn add_at<T>(self, prefix: &str, app: T) -> Self
+    where
+        T: IntoAppObject<A>,
+    {
+        self.insert(AppId::of::<T>(), app, Some(prefix.to_string()), None)
+    }
+
+    /// Registers an app like [`Reactor::add`], which receives its config from the settings of the
+    /// reactor before `pre_init`
+    pub fn add_configured<T>(self, app: T) -> Self
+    where
+        T: Configurable + IntoAppObject<A>,
+    {
+        self.insert(AppId::of::<T>(), app, None, Some(config::loader::<T>()))
+    }
+
+    /// Registers a named instance of a configurable app, like [`Reactor::add_named`]. The instance
+    /// reads its config from the table named after it in the section of the app, like
+    /// `[auth.staff]`.
+    pub fn add_configured_named<T>(self, name: &'static str, app: T) -> Self
+    where
+        T: Configurable + IntoAppObject<A>,
+    {
+        let loader = Some(config::loader::<T>());
+        self.insert(AppId::named::<T>(name), app, None, loader)
+    }
+
+    /// Registers a configurable app like [`Reactor::add_configured`], mounting its web services
+    /// under `prefix`
+    pub fn add_configured_at<T>(self, prefix: &str, app: T) -> Self
+    where
+        T: Configurable + IntoAppObject<A>,
+    {
+        let mount = Some(prefix.to_string());
+        self.insert(AppId::of::<T>(), app, mount, Some(config::loader::<T>()))
+    }
+
+    /// Registers a named instance of a configurable app like [`Reactor::add_configured_named`],
+    /// mounting its web services under `prefix`
+    pub fn add_configured_named_at<T>(self, name: &'static str, prefix: &str, app: T) -> Self
+    where
+        T: Configurable + IntoAppObject<A>,
+    {
+        let mount = Some(prefix.to_string());
+        let loader = Some(config::loader::<T>());
+        self.insert(AppId::named::<T>(name), app, mount, loader)
+    }
+
+    /// Replaces a registered app, keeping its place, mount point and config. Used by tests to
+    /// swap an app for another instance of the same type.
+    ///
+    /// Replacing an app which is not registered is reported by [`Reactor::try_build`].
+    pub fn replace<T>(self, app: T) -> Self
+    where
+        T: IntoAppObject<A>,
+    {
+        self.replace_instance(AppId::of::<T>(), app)
+    }
+
+    /// Replaces an instance registered with [`Reactor::add_named`], like [`Reactor::replace`]
+    pub fn replace_named<T>(self, name: &'static str, app: T) -> Self
+    where
+        T: IntoAppObject<A>,
+    {
+        self.replace_instance(AppId::named::<T>(name), app)
+    }
+
+    fn replace_instance<T>(mut self, id: AppId, app: T) -> Self
+    where
+        T: IntoAppObject<A>,
+    {
+        match self.apps.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.app = app.into_object();
+                entry.stand_in = None;
+            }
+            None => self
+                .errors
+                .push(BuildError::NotRegistered { app: id.name() }),
+        }
         self
     }
 
-    pub fn build(mut self) -> Self {
-        for (_, app) in self.apps.iter_mut() {
-            app.init(&mut self.registry);
+    /// Replaces a registered app `T` with a stand-in of another type, like a fake mail app in
+    /// tests.
+    ///
+    /// The stand-in takes the place and the mount point of `T`, and satisfies the apps declaring
+    /// `T` as their dependency. The stand-in can't be handed to the DI hooks asking for `T`, the
+    /// optional ones receive `None` and the others are reported by [`Reactor::try_build`], while
+    /// hooks can still ask for the stand-in by its own type. Once frozen, the stand-in is found by
+    /// its own type too, like `reactor.get::<FakeMailer>()`.
+    ///
+    /// Replacing an app which is not registered is reported by [`Reactor::try_build`].
+    pub fn stand_in<T, S>(mut self, app: S) -> Self
+    where
+        T: App,
+        S: IntoAppObject<A>,
+    {
+        let id = AppId::of::<T>();
+        match self.apps.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.app = app.into_object();
+                entry.loader = None;
+                entry.stand_in = Some(AppId::of::<S>());
+            }
+            None => self
+                .errors
+                .push(BuildError::NotRegistered { app: id.name() }),
         }
+        self
+    }
 
-        self.run_hooks()
+    /// Sets the settings the configurable apps read their config from
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
     }
 
-    fn run_hooks(mut self) -> Self {
-        for injector in self.registry.di.clone().iter() {
-            injector.run(&mut self)
+    fn insert<T>(mut self, id: AppId, app: T, mount: Option<String>, loader: Option<Loader>) -> Self
+    where
+        T: IntoAppObject<A>,
+    {
+        match self.apps.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.app = app.into_object();
+                entry.mount = mount;
+                entry.loader = loader;
+                entry.stand_in = None;
+            }
+            None => {
+                self.order.push(self.apps.len());
+                self.apps.push(Entry {
+                    id,
+                    app: app.into_object(),
+                    mount,
+                    loader,
+                    stand_in: None,
+                    middleware: MiddlewareConfig::default(),
+                });
+            }
         }
         self
     }
+
+    /// Runs the lifecycle of all the registered apps, see [`Reactor::try_build`].
+    ///
+    /// # Panics
+    ///
+    /// Panics listing all the problems if the reactor fails to build.
+    pub fn build(self) -> Self {
+        self.try_build().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Runs the lifecycle of all the registered apps.
+    ///
+    /// The dependencies declared by the apps are checked first, and every missing app and
+    /// dependency cycle is reported before any of the hooks is run.
+    ///
+    /// `pre_init` and `init` are called on the apps in the order they were added, but after the
+    /// apps they declare as dependencies. Then the DI hooks registered during `init` are checked,
+    /// reporting all the missing and duplicated parameters at once, and run in the order they were
+    /// registered. Afterwards the apps are sorted again to take the dependencies of their hooks
+    /// into account, and this order is used for `post_init` and for configuring the web app.
+    ///
+    /// The async variants of the lifecycle hooks are polled only once, the ones that have to wait
+    /// are reported as errors and dropped half-run, keeping whatever they did before waiting. Async
+    /// DI hooks are reported before any hook is run instead. Use [`Reactor::try_build_async`] for
+    /// apps doing I/O during startup.
+    pub fn try_build(self) -> Result<Self, ReactorError> {
+        self.lifecycle(true)
+            .now_or_never()
+            .expect("The blocking lifecycle never waits")
+    }
+
+    /// Async version of [`Reactor::build`]
+    pub async fn build_async(self) -> Self {
+        self.try_build_async()
+            .await
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Async version of [`Reactor::try_build`], awaits the async lifecycle hooks and DI hooks of
+    /// the apps one at a time, in the same order.
+    pub async fn try_build_async(self) -> Result<Self, ReactorError> {
+        self.lifecycle(false).await
+    }
+
+    async fn lifecycle(mut self, blocking: bool) -> Result<Self, ReactorError> {
+        let mut errors = std::mem::take(&mut self.errors);
+        let (deps, dep_errors) = self.declared_dependencies();
+        errors.extend(dep_errors);
+        let sorted = graph::sort(&deps);
+        errors.extend(sorted.cycles.iter().map(|cycle| self.cycle_error(cycle)));
+        errors.extend(self.load_configs());
+        errors.extend(self.collect_middleware());
+        check(errors)?;
+        self.order = sorted.order;
+
+        let mut errors = Vec::new();
+        for &idx in self.order.iter() {
+            let entry = &mut self.apps[idx];
+            let span = trace::phase("pre_init", entry.id);
+            if step(entry.app.pre_init_async().instrument(span), blocking)
+                .await
+                .is_none()
+            {
+                errors.push(blocked(entry.id, "pre_init"));
+            }
+        }
+        check(errors)?;
+
+        let mut errors = Vec::new();
+        for &idx in self.order.iter() {
+            let entry = &mut self.apps[idx];
+            self.registry.current = Some((idx, entry.id));
+            let span = trace::phase("init", entry.id);
+            let init = entry.app.init_async(&mut self.registry).instrument(span);
+            if step(init, blocking).await.is_none() {
+                errors.push(blocked(entry.id, "init"));
+            }
+        }
+        self.registry.current = None;
+        check(errors)?;
+
+        self.run_hooks(blocking).await?;
+        self.order = graph::sort(&self.dependencies()).order;
+
+        let mut errors = Vec::new();
+        for &idx in self.order.iter() {
+            let entry = &mut self.apps[idx];
+            let span = trace::phase("post_init", entry.id);
+            if step(entry.app.post_init_async().instrument(span), blocking)
+                .await
+                .is_none()
+            {
+                errors.push(blocked(entry.id, "post_init"));
+            }
+        }
+        check(errors)?;
+
+        let subscribers = std::mem::take(&mut self.registry.subscribers);
+        self.events = Events::new(subscribers);
+        self.tasks = std::mem::take(&mut self.registry.tasks);
+        self.metrics
+            .add_counters(std::mem::take(&mut self.registry.counters));
+        Ok(self)
+    }
+
+    /// The dependency graph of the apps, including the dependencies declared by the apps and the
+    /// apps asked for by their DI hooks.
+    ///
+    /// DI hooks are registered while the apps are initialized, so the graph should be taken once
+    /// the reactor is built to include them. The apps are listed in the order they were added.
+    pub fn graph(&self) -> DependencyGraph {
+        let apps = self
+            .apps
+            .iter()
+            .map(|entry| GraphNode {
+                name: entry.id.name(),
+                instance: entry.id.instance(),
+            })
+            .collect();
+
+        let mut edges = Vec::new();
+        let (deps, _) = self.declared_dependencies();
+        for (from, app_deps) in deps.into_iter().enumerate() {
+            edges.extend(app_deps.into_iter().map(|to| GraphEdge {
+                from,
+                to,
+                hook: None,
+                mutable: false,
+                optional: false,
+            }));
+        }
+        for hook in self.registry.di.iter() {
+            let from = match hook.owner {
+                Some(owner) => owner,
+                None => continue,
+            };
+            for param in hook.params.iter() {
+                match self.position(param.app) {
+                    // hooks asking for their own app don't couple it to others
+                    Some(to) if to != from => edges.push(GraphEdge {
+                        from,
+                        to,
+                        hook: Some(hook.name.clone()),
+                        mutable: param.mutable,
+                        optional: param.optional,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+
+        DependencyGraph { apps, edges }
+    }
+
+    /// The event bus of the reactor, it's available once the reactor is built
+    pub fn events(&self) -> &Events {
+        &self.events
+    }
+
+    /// The middleware contributed by the apps to wrap the whole web app, see
+    /// [`App::configure_middleware`].
+    ///
+    /// The middleware of the apps are chained in their initialization order, the ones of the
+    /// first app being the outermost, and the middleware of each app in the order it added them.
+    /// They are applied by [`ActixMiddlewareExt::wrap_reactor`], along with the recorder of the
+    /// metrics:
+    ///
+    /// ```ignore
+    /// App::new().configure_app(&reactor).wrap_reactor(&reactor)
+    /// ```
+    ///
+    /// It's available once the reactor is built.
+    pub fn middleware(&self) -> ReactorMiddleware {
+        global_middleware(&self.apps, &self.order, &self.metrics)
+    }
+
+    /// Calls `finish` on every app in the reverse order of their initialization, should be called
+    /// once the reactor is not needed anymore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the async `finish` hook of an app has to wait, use [`Reactor::finish_async`] for
+    /// those apps.
+    pub fn finish(self) {
+        let blocked = self
+            .finish_apps(true)
+            .now_or_never()
+            .expect("The blocking lifecycle never waits");
+
+        if !blocked.is_empty() {
+            panic!(
+                "Apps {} have to wait in `finish`, use `finish_async` instead",
+                blocked.join(", ")
+            )
+        }
+    }
+
+    /// Async version of [`Reactor::finish`]
+    pub async fn finish_async(self) {
+        self.finish_apps(false).await;
+    }
+
+    /// Like [`Reactor::finish_async`], but gives up on the apps which are still finishing once
+    /// `limit` is elapsed. Their `finish` is dropped half-run, and the next apps are finished in
+    /// the time left, the ones which don't have to wait are finished even when it's up.
+    pub async fn finish_within(mut self, limit: Duration) -> Result<(), ShutdownError> {
+        let deadline = Instant::now() + limit;
+        let mut timed_out = Vec::new();
+        for &idx in self.order.iter().rev() {
+            let entry = &mut self.apps[idx];
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let finish = entry
+                .app
+                .finish_async()
+                .instrument(trace::phase("finish", entry.id));
+            if timeout(remaining, finish).await.is_err() {
+                timed_out.push(entry.id.name());
+            }
+        }
+        if timed_out.is_empty() {
+            Ok(())
+        } else {
+            Err(ShutdownError::TimedOut { apps: timed_out })
+        }
+    }
+
+    /// Finishes all the apps, returning the ones which had to wait in blocking mode
+    async fn finish_apps(mut self, blocking: bool) -> Vec<String> {
+        let mut blocked = Vec::new();
+        for &idx in self.order.iter().rev() {
+            let entry = &mut self.apps[idx];
+            let span = trace::phase("finish", entry.id);
+            if step(entry.app.finish_async().instrument(span), blocking)
+                .await
+                .is_none()
+            {
+                blocked.push(format!("`{}`", entry.id.name()));
+            }
+        }
+        blocked
+    }
+
+    /// The migrations of the apps, ordered by the dependencies the apps declare, see
+    /// [`App::migrations`].
+    ///
+    /// The reactor doesn't have to be built, missing apps and dependency cycles are reported like
+    /// [`Reactor::try_build`] does, along with the apps with migrations and no
+    /// [`App::migrations_namespace`], and the namespaces declared more than once.
+    pub fn migrator(&self) -> Result<Migrator, ReactorError> {
+        let (deps, mut errors) = self.declared_dependencies();
+        let sorted = graph::sort(&deps);
+        errors.extend(sorted.cycles.iter().map(|cycle| self.cycle_error(cycle)));
+
+        let mut apps: Vec<(&'static str, &'static str, _)> = Vec::new();
+        for entry in ordered(&self.apps, &sorted.order) {
+            let migrations = entry.app.migrations();
+            if migrations.is_empty() {
+                continue;
+            }
+            let app = entry.own_id().name();
+            let namespace = match entry.app.migrations_namespace() {
+                Some(namespace) => namespace,
+                None => {
+                    errors.push(BuildError::UnlabeledMigrations { app });
+                    continue;
+                }
+            };
+            if let Some((prev, ..)) = apps.iter().find(|(_, prev, _)| *prev == namespace) {
+                errors.push(BuildError::DuplicateNamespace {
+                    namespace,
+                    apps: (*prev, app),
+                });
+                continue;
+            }
+            apps.push((app, namespace, migrations));
+        }
+        check(errors)?;
+
+        let apps = apps
+            .into_iter()
+            .map(|(_, namespace, migrations)| (namespace, migrations))
+            .collect();
+        Ok(Migrator::new(apps))
+    }
+
+    /// The help of the management commands returned by [`SharedReactor::run_cli`], the reactor should be
+    /// built as the apps add their commands once they are initialized
+    pub fn cli_help(&self, program: &str) -> Result<String, CliError> {
+        Ok(self.cli(program)?.help())
+    }
+
+    /// Collects the commands of the apps in the order they were added
+    fn cli(&self, program: &str) -> Result<Cli, CliError> {
+        let program = Path::new(program)
+            .file_name()
+            .map_or_else(|| program.into(), |name| name.to_string_lossy());
+        let mut commands = Vec::new();
+        for entry in ordered(&self.apps, &self.order) {
+            let mut cmds = Commands::new(entry.own_id());
+            entry.app.configure_commands(&mut cmds);
+            commands.extend(cmds.commands);
+        }
+        Cli::new(program.into_owned(), commands)
+    }
+
+    fn position(&self, id: AppId) -> Option<usize> {
+        self.apps.iter().position(|entry| entry.matches(id))
+    }
+
+    /// The indices of the apps each app declares as its dependencies, along with the missing ones
+    fn declared_dependencies(&self) -> (Vec<Vec<usize>>, Vec<BuildError>) {
+        let mut deps = Vec::with_capacity(self.apps.len());
+        let mut errors = Vec::new();
+        for entry in self.apps.iter() {
+            let mut app_deps = Vec::new();
+            for dep in entry.app.dependencies() {
+                match self.position(dep) {
+                    Some(idx) => app_deps.push(idx),
+                    None => errors.push(BuildError::MissingApp {
+                        app: entry.id.name(),
+                        dependency: dep.name(),
+                        instance: dep.instance(),
+                    }),
+                }
+            }
+            deps.push(app_deps);
+        }
+        (deps, errors)
+    }
+
+    /// Hands the configurable apps their config, returning the sections which failed to load
+    fn load_configs(&mut self) -> Vec<BuildError> {
+        let settings = &self.settings;
+        let named: Vec<AppId> = self
+            .apps
+            .iter()
+            .map(|entry| entry.id)
+            .filter(|id| id.instance().is_some())
+            .collect();
+        self.apps
+            .iter_mut()
+            .filter_map(|entry| {
+                let load = entry.loader?;
+                let _span = trace::phase("config", entry.id).entered();
+                // the tables of the named instances are nested in the section of the unnamed one
+                let instances: Vec<_> = match entry.id.instance() {
+                    Some(_) => Vec::new(),
+                    None => named
+                        .iter()
+                        .filter(|id| id.is_instance_of(&entry.id))
+                        .filter_map(AppId::instance)
+                        .collect(),
+                };
+                load(
+                    entry.app.as_app_mut(),
+                    settings,
+                    entry.id.instance(),
+                    &instances,
+                )
+                .err()
+                .map(|message| BuildError::InvalidConfig {
+                    app: entry.id.name(),
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    /// Collects the middleware of the apps, returning the apps with scoped middleware and no
+    /// mount point to scope them to
+    fn collect_middleware(&mut self) -> Vec<BuildError> {
+        let mut errors = Vec::new();
+        for entry in self.apps.iter_mut() {
+            let mut middleware = MiddlewareConfig::default();
+            entry.app.configure_middleware(&mut middleware);
+            entry.middleware = middleware;
+            if !entry.middleware.scoped.is_empty() && entry.prefix().is_empty() {
+                errors.push(BuildError::UnmountedMiddleware {
+                    app: entry.id.name(),
+                });
+            }
+        }
+        errors
+    }
+
+    fn cycle_error(&self, cycle: &[usize]) -> BuildError {
+        BuildError::Cycle(cycle.iter().map(|&idx| self.apps[idx].id.name()).collect())
+    }
+
+    /// The indices of the apps each app depends on, as declared by the apps and their DI hooks
+    fn dependencies(&self) -> Vec<Vec<usize>> {
+        let (mut deps, _) = self.declared_dependencies();
+        for hook in self.registry.di.iter() {
+            if let Some(owner) = hook.owner {
+                deps[owner].extend(
+                    hook.params
+                        .iter()
+                        .filter_map(|param| self.position(param.app)),
+                );
+            }
+        }
+        deps
+    }
+
+    /// The name of the app which registered the hook
+    fn owner_name(&self, hook: &Hook) -> &'static str {
+        hook.owner
+            .map(|idx| self.apps[idx].id.name())
+            .unwrap_or("<unknown>")
+    }
+
+    /// Checks the parameters of all the hooks before running any of them, and then runs them all
+    /// collecting their errors.
+    async fn run_hooks(&mut self, blocking: bool) -> Result<(), ReactorError> {
+        // hooks rejected at registration
+        let mut errors = std::mem::take(&mut self.registry.errors);
+        for hook in self.registry.di.iter() {
+            for param in hook.params.iter().filter(|param| !param.optional) {
+                match self.position(param.app).map(|idx| &self.apps[idx]) {
+                    None => errors.push(BuildError::MissingParam {
+                        app: self.owner_name(hook),
+                        hook: hook.name.clone(),
+                        param: param.app.name(),
+                        instance: param.app.instance(),
+                    }),
+                    // the stand-in can't be handed to the hook
+                    Some(entry) if entry.is_replaced(param.app) => {
+                        errors.push(BuildError::ReplacedParam {
+                            app: self.owner_name(hook),
+                            hook: hook.name.clone(),
+                            param: param.app.name(),
+                            instance: param.app.instance(),
+                            stand_in: entry.own_id().name(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        check(errors)?;
+
+        // a DI hook dropped half-run could leave the apps it borrows in an inconsistent state
+        if blocking {
+            let errors = self
+                .registry
+                .di
+                .iter()
+                .filter(|hook| hook.asynchronous)
+                .map(|hook| BuildError::BlockedHook {
+                    app: self.owner_name(hook),
+                    hook: hook.name.clone(),
+                })
+                .collect();
+            check(errors)?;
+        }
+
+        let mut errors = Vec::new();
+        for hook in self.registry.di.clone().iter() {
+            let span = trace::inject(self.owner_name(hook), &hook.name);
+            let run = hook.injector.run(Loan::new(&mut self.apps));
+            let err = match step(run.instrument(span), blocking).await {
+                Some(Ok(())) => continue,
+                None => BuildError::BlockedHook {
+                    app: self.owner_name(hook),
+                    hook: hook.name.clone(),
+                },
+                Some(Err(HookError::Missing(param))) => BuildError::MissingParam {
+                    app: self.owner_name(hook),
+                    hook: hook.name.clone(),
+                    param: param.name(),
+                    instance: param.instance(),
+                },
+                Some(Err(HookError::Failed(message))) => BuildError::HookFailed {
+                    app: self.owner_name(hook),
+                    hook: hook.name.clone(),
+                    message,
+                },
+            };
+            errors.push(err);
+        }
+        check(errors)
+    }
+}
+
+fn check(errors: Vec<BuildError>) -> Result<(), ReactorError> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ReactorError::new(errors))
+    }
+}
+
+fn blocked(app: AppId, step: &'static str) -> BuildError {
+    BuildError::Blocked {
+        app: app.name(),
+        step,
+    }
+}
+
+/// Waits for a lifecycle step, in blocking mode the step is only polled once and `None` is
+/// returned if it has to wait
+async fn step<F>(fut: F, blocking: bool) -> Option<F::Output>
+where
+    F: Future,
+{
+    if blocking {
+        fut.now_or_never()
+    } else {
+        Some(fut.await)
+    }
+}
+
+/// Iterates over the apps in their initialization order
+fn ordered<'a, A>(apps: &'a [Entry<A>], order: &'a [usize]) -> impl Iterator<Item = &'a Entry<A>>
+where
+    A: ?Sized,
+{
+    order.iter().map(move |&idx| &apps[idx])
+}
+
+/// Configures the web services of the apps, the mounted ones in a scope under their mount point
+///
+/// An empty scope catches every request, so the root apps are only scoped when their requests are
+/// attributed, by the metrics or the traces. They are then configured after the mounted apps, each
+/// one nested in the scope of the previous one so the requests it doesn't match fall through.
+fn configure_web<'a, A>(
+    entries: impl Iterator<Item = &'a Entry<A>>,
+    metrics: &Metrics,
+    cfg: &mut ServiceConfig,
+) where
+    A: ?Sized + AppObject + 'a,
+{
+    let (roots, mounted): (Vec<_>, Vec<_>) = entries.partition(|entry| entry.prefix().is_empty());
+    for entry in mounted {
+        entry.configure_web(cfg, &[]);
+    }
+    if metrics.is_recording() || trace::REQUESTS {
+        configure_roots(&roots, cfg);
+    } else {
+        for entry in roots {
+            entry.app.configure_web(cfg);
+        }
+    }
+}
+
+/// Each root app falls through to the next one, the last one to the default service
+fn configure_roots<A>(roots: &[&Entry<A>], cfg: &mut ServiceConfig)
+where
+    A: ?Sized + AppObject,
+{
+    if let Some((entry, rest)) = roots.split_first() {
+        entry.configure_web(cfg, rest);
+    }
+}
+
+fn global_middleware<A>(apps: &[Entry<A>], order: &[usize], metrics: &Metrics) -> ReactorMiddleware
+where
+    A: ?Sized + AppObject,
+{
+    // the recorder is the outermost, to time the middleware of the apps too
+    let middleware = metrics
+        .recorder()
+        .into_iter()
+        .chain(ordered(apps, order).flat_map(|entry| entry.middleware.global.iter().cloned()))
+        .collect();
+    ReactorMiddleware::new(middleware)
+}
+
+struct Frozen {
+    apps: Vec<Entry<dyn App + Send + Sync>>,
+    // the hooks are dropped once frozen
+    graph: DependencyGraph,
+    order: Vec<usize>,
+    events: Events,
+    tasks: Mutex<Tasks>,
+    health: Option<HealthEndpoint>,
+    metrics: Metrics,
+    // signalled whenever a clone of the reactor is dropped, see `FrozenReactor::shutdown`
+    released: mpsc::UnboundedSender<()>,
+    on_release: Mutex<Option<mpsc::UnboundedReceiver<()>>>,
+}
+
+/// Signals `shutdown` once the clone holding it is dropped
+#[derive(Clone)]
+struct Release(mpsc::UnboundedSender<()>);
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        let _ = self.0.unbounded_send(());
+    }
+}
+
+/// A built reactor shared between the workers of an actix server, cloning it is cheap
+///
+/// Created by [`Reactor::freeze`].
+#[derive(Clone)]
+pub struct FrozenReactor {
+    inner: Arc<Frozen>,
+    // dropped after `inner`, so the reactor is released when it's signalled
+    _release: Release,
+}
+
+impl FrozenReactor {
+    fn new(inner: Arc<Frozen>) -> Self {
+        let release = Release(inner.released.clone());
+        Self {
+            inner,
+            _release: release,
+        }
+    }
+
+    /// The built instance of an app, `None` if it is not registered or replaced by a stand-in of
+    /// another type, see [`Reactor::stand_in`]
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: App,
+    {
+        self.get_instance(AppId::of::<T>())
+    }
+
+    /// The built instance of an app added with [`Reactor::add_named`]
+    pub fn get_named<T>(&self, name: &'static str) -> Option<&T>
+    where
+        T: App,
+    {
+        self.get_instance(AppId::named::<T>(name))
+    }
+
+    fn get_instance<T>(&self, id: AppId) -> Option<&T>
+    where
+        T: App,
+    {
+        self.position::<T>(id).and_then(|idx| self.at(idx))
+    }
+
+    /// The index of the built instance of an app, `None` if it is not registered
+    pub(crate) fn position<T>(&self, id: AppId) -> Option<usize>
+    where
+        T: App,
+    {
+        self.inner
+            .apps
+            .iter()
+            .position(|entry| entry.matches(id) && entry.app.as_app().is::<T>())
+    }
+
+    /// The identity of the stand-in which replaced the app `id`
+    pub(crate) fn stand_in(&self, id: AppId) -> Option<AppId> {
+        self.inner
+            .apps
+            .iter()
+            .find(|entry| entry.is_replaced(id))
+            .map(Entry::own_id)
+    }
+
+    /// The app at an index returned by [`FrozenReactor::position`]
+    pub(crate) fn at<T>(&self, idx: usize) -> Option<&T>
+    where
+        T: App,
+    {
+        self.inner.apps[idx].app.as_app().downcast_ref()
+    }
+
+    /// The dependency graph of the apps, as it was when the reactor was frozen, see
+    /// [`Reactor::graph`]
+    pub fn graph(&self) -> &DependencyGraph {
+        &self.inner.graph
+    }
+
+    /// The event bus of the reactor
+    pub fn events(&self) -> &Events {
+        &self.inner.events
+    }
+
+    /// The middleware contributed by the apps, see [`Reactor::middleware`]
+    pub fn middleware(&self) -> ReactorMiddleware {
+        global_middleware(&self.inner.apps, &self.inner.order, &self.inner.metrics)
+    }
+
+    /// The metrics of the apps in the Prometheus text format, see
+    /// [`Reactor::with_metrics_endpoint`]
+    pub fn metrics(&self) -> String {
+        self.inner.metrics.render()
+    }
+
+    /// Runs the health checks of the apps concurrently, see [`Reactor::with_health_endpoint`]
+    pub async fn health(&self, limit: Duration) -> HealthReport {
+        health::check(ordered(&self.inner.apps, &self.inner.order), limit).await
+    }
+
+    /// Starts the background tasks and the scheduled jobs of the apps on the current arbiter, see
+    /// [`Registry::spawn`].
+    ///
+    /// Tasks are started only once, no matter how many times this is called. It has to be called
+    /// from within an actix system, usually before starting the server.
+    pub fn start_tasks(&self) {
+        self.inner.tasks.lock().unwrap().start(self);
+    }
+
+    /// Cancels the running background tasks, and waits until all of them are dropped.
+    ///
+    /// The tasks hold clones of the reactor, so they have to be stopped before unfreezing it.
+    pub async fn stop_tasks(&self) {
+        let stopped = self.inner.tasks.lock().unwrap().stop();
+        stopped.await
+    }
+
+    /// A handle which doesn't keep the reactor alive, see [`WeakReactor`]
+    pub fn weak(&self) -> WeakReactor {
+        WeakReactor {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// Waits for an actix server to stop, by a signal or by [`Server::stop`], and shuts the
+    /// reactor down, see [`FrozenReactor::shutdown`].
+    ///
+    /// The server factory has to configure the apps using a [`WeakReactor`]:
+    ///
+    /// ```ignore
+    /// let weak = reactor.weak();
+    /// let server = HttpServer::new(move || App::new().configure_app(&weak))
+    ///     .bind("127.0.0.1:8000")?
+    ///     .run();
+    /// reactor.serve(server, Duration::from_secs(30)).await
+    /// ```
+    pub async fn serve(self, server: Server, limit: Duration) -> io::Result<()> {
+        let stopped = server.await;
+        let finished = self.shutdown(limit).await;
+        stopped?;
+        finished.map_err(io::Error::other)
+    }
+
+    /// Stops the background tasks, waits for the other clones of the reactor to be dropped, and
+    /// calls `finish` on the apps in the reverse order of their initialization, all within `limit`.
+    ///
+    /// The workers of a stopped actix server drop their clones shortly after the server stops.
+    pub async fn shutdown(self, limit: Duration) -> Result<(), ShutdownError> {
+        let deadline = Instant::now() + limit;
+        self.stop_tasks().await;
+
+        // taken by the first clone shutting the reactor down, the others can't get it back
+        let mut on_release = match self.inner.on_release.lock().unwrap().take() {
+            Some(on_release) => on_release,
+            None => return Err(ShutdownError::Shared),
+        };
+        let mut frozen = self;
+        let reactor = loop {
+            match frozen.unfreeze() {
+                Ok(reactor) => break reactor,
+                Err(shared) => {
+                    frozen = shared;
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if timeout(remaining, on_release.next()).await.is_err() {
+                        return Err(ShutdownError::Shared);
+                    }
+                }
+            }
+        };
+        reactor
+            .finish_within(deadline.saturating_duration_since(Instant::now()))
+            .await
+    }
+
+    /// Gets the reactor back once all the other clones are dropped, usually after the server and
+    /// the background tasks are stopped, so the apps can be finished.
+    pub fn unfreeze(self) -> Result<SharedReactor, Self> {
+        // keeping the release of the clone, `shutdown` is only signalled by the other clones
+        let Self { inner, _release } = self;
+        match Arc::try_unwrap(inner) {
+            Ok(frozen) => Ok(Reactor {
+                apps: frozen.apps,
+                order: frozen.order,
+                registry: Registry::default(),
+                settings: Settings::default(),
+                events: frozen.events,
+                tasks: frozen.tasks.into_inner().unwrap().into_pending(),
+                health: frozen.health,
+                metrics: frozen.metrics,
+                errors: Vec::new(),
+            }),
+            Err(inner) => Err(Self { inner, _release }),
+        }
+    }
+}
+
+/// A handle to a frozen reactor which doesn't keep it alive, used by the actix server factory
+///
+/// The factory of an actix server is kept until the process exits, so capturing the reactor
+/// itself would prevent [`FrozenReactor::shutdown`] from finishing the apps.
+#[derive(Clone)]
+pub struct WeakReactor {
+    inner: Weak<Frozen>,
+}
+
+impl WeakReactor {
+    /// The reactor, `None` once it's shut down
+    pub fn upgrade(&self) -> Option<FrozenReactor> {
+        self.inner.upgrade().map(FrozenReactor::new)
+    }
+}
+
+#[derive(Clone)]
+struct Hook {
+    // the types of the apps it asks for, see `di::signature`
+    name: String,
+    // the app which registered the hook
+    owner: Option<usize>,
+    asynchronous: bool,
+    params: Vec<Param>,
+    injector: Rc<dyn Injector>,
 }
 
 #[derive(Default)]
 pub struct Registry {
     // different hooks can be defined here, like on config change, on new app register etc
-    di: Vec<Rc<dyn Injector>>,
+    di: Vec<Hook>,
+    subscribers: Subscribers,
+    tasks: Vec<Task>,
+    // whether the reactor can be frozen to run the tasks
+    shared: bool,
+    counters: Vec<AppCounter>,
+    // the app being initialized
+    current: Option<(usize, AppId)>,
+    errors: Vec<BuildError>,
 }
 
 impl Registry {
+    /// Checks the reactor can run the background tasks, recording the tasks it can't
+    fn check_shared(&mut self) -> bool {
+        if !self.shared {
+            self.errors.push(BuildError::UnsharedTask {
+                app: self.current.map_or("<unknown>", |(_, app)| app.name()),
+            });
+        }
+        self.shared
+    }
+
+    /// Checks the params of a hook don't conflict with each other, recording the conflicts
+    fn check_params(&mut self, name: &str, params: &[Param]) -> bool {
+        // each app is only reported once, no matter how many times it's repeated
+        let mut reported: Vec<AppId> = Vec::new();
+        for (idx, param) in params.iter().enumerate() {
+            if reported.contains(&param.app) {
+                continue;
+            }
+            if params[..idx].iter().any(|prev| prev.conflicts(param)) {
+                reported.push(param.app);
+                self.errors.push(BuildError::DuplicateParam {
+                    app: self.current.map_or("<unknown>", |(_, app)| app.name()),
+                    hook: name.to_string(),
+                    param: param.app.name(),
+                });
+            }
+        }
+        reported.is_empty()
+    }
+
+    /// Registers a DI hook, which is run once all the apps are initialized.
+    ///
+    /// A hook asking for the same app more than once while borrowing it mutably is rejected, the
+    /// error is reported by [`Reactor::try_build`] before any hook is run.
     pub fn register_di<F, P>(&mut self, inject_fn: F)
     where
         F: InjectFactory<P> + 'static,
         P: 'static,
     {
-        self.di.push(Rc::new(Injectable::new(inject_fn)))
+        let params = F::params();
+        let name = di::signature(&params);
+        if self.check_params(&name, &params) {
+            self.di.push(Hook {
+                name,
+                owner: self.current.map(|(idx, _)| idx),
+                asynchronous: false,
+                params,
+                injector: Rc::new(Injectable::new(inject_fn)),
+            })
+        }
+    }
+
+    /// Registers an async DI hook, the hook returns a boxed future which can borrow the apps it
+    /// asks for.
+    ///
+    /// The reactor has to be built with [`Reactor::build_async`], building it synchronously reports
+    /// the async hooks without running any hook.
+    pub fn register_di_async<F, P>(&mut self, inject_fn: F)
+    where
+        F: AsyncInjectFactory<P> + 'static,
+        P: 'static,
+    {
+        let params = F::params();
+        let name = di::signature(&params);
+        if self.check_params(&name, &params) {
+            self.di.push(Hook {
+                name,
+                owner: self.current.map(|(idx, _)| idx),
+                asynchronous: true,
+                params,
+                injector: Rc::new(AsyncInjectable::new(inject_fn)),
+            })
+        }
+    }
+
+    /// Subscribes to an event, the subscribers of an event are called from the highest priority
+    /// to the lowest, and in the order they were subscribed for the same priority.
+    ///
+    /// Events are published at runtime by the request handlers of the apps, using [`Events`].
+    pub fn subscribe<E, F>(&mut self, priority: i32, handler: F)
+    where
+        E: Event,
+        F: Fn(&E) + Send + Sync + 'static,
+    {
+        self.subscribers.subscribe(priority, handler)
+    }
+
+    /// Subscribes to an event with an async handler, it's awaited before the next subscriber is
+    /// called.
+    pub fn subscribe_async<E, F, Fut>(&mut self, priority: i32, handler: F)
+    where
+        E: Event,
+        F: Fn(Arc<E>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.subscribers.subscribe_async(priority, handler)
+    }
+
+    /// Registers a background task, like a queue consumer, it's spawned on the actix runtime by
+    /// [`FrozenReactor::start_tasks`] and receives the frozen reactor to access the apps.
+    ///
+    /// Long running tasks are cancelled by [`FrozenReactor::stop_tasks`], at any of their
+    /// `.await` points. Only the shared reactors can be frozen, the tasks registered on the
+    /// others are reported by [`Reactor::try_build`].
+    pub fn spawn<F, Fut>(&mut self, task: F)
+    where
+        F: FnOnce(FrozenReactor) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        if self.check_shared() {
+            self.tasks
+                .push(Box::new(move |reactor| Box::pin(task(reactor))))
+        }
+    }
+
+    /// Registers a job run periodically as a background task, like expiring the sessions every
+    /// hour, see [`Registry::spawn`].
+    ///
+    /// A job is run again only once its previous run is done.
+    pub fn schedule<F, Fut>(&mut self, schedule: Schedule, job: F)
+    where
+        F: Fn(FrozenReactor) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        if self.check_shared() {
+            self.tasks.push(tasks::scheduled(schedule, job))
+        }
+    }
+
+    /// Registers a counter of the app being initialized, like the number of sent emails, reported
+    /// by the metrics endpoint with an `app` label, see [`Reactor::with_metrics_endpoint`].
+    ///
+    /// The name should be a valid Prometheus metric name, like `emails_sent_total`. Registering
+    /// the same name again in the same app returns the same counter.
+    pub fn counter(&mut self, name: &'static str, help: &'static str) -> Counter {
+        let (app, instance) = self
+            .current
+            .map_or(("<unknown>", None), |(_, app)| (app.name(), app.instance()));
+        let existing = self
+            .counters
+            .iter()
+            .find(|counter| (counter.app, counter.instance, counter.name) == (app, instance, name));
+        if let Some(existing) = existing {
+            return existing.counter.clone();
+        }
+
+        let counter = Counter::default();
+        self.counters.push(AppCounter {
+            app,
+            instance,
+            name,
+            help,
+            counter: counter.clone(),
+        });
+        counter
+    }
+}
+
+/// Reactors which can configure the web services of their apps
+pub trait ConfigureApps {
+    /// Calls `configure_web` on all the apps in their initialization order, each app is wrapped in
+    /// a scope if it has a mount point, along with its scoped middleware
+    ///
+    /// When the requests are attributed to the apps, by the metrics endpoint or the `tracing`
+    /// feature, the root apps are scoped too and catch the requests which none of the services
+    /// registered before them match, so the own services of the web app must be registered first.
+    fn configure_apps(&self, cfg: &mut ServiceConfig);
+
+    /// The middleware wrapping the whole web app, see [`Reactor::middleware`]
+    fn middleware(&self) -> ReactorMiddleware;
+}
+
+impl<A> ConfigureApps for Reactor<A>
+where
+    A: ?Sized + AppObject,
+{
+    fn configure_apps(&self, cfg: &mut ServiceConfig) {
+        cfg.app_data(self.events.clone());
+        if let Some(health) = &self.health {
+            health.configure(cfg);
+        }
+        self.metrics.configure(cfg);
+        configure_web(ordered(&self.apps, &self.order), &self.metrics, cfg);
+    }
+
+    fn middleware(&self) -> ReactorMiddleware {
+        Reactor::middleware(self)
+    }
+}
+
+impl ConfigureApps for FrozenReactor {
+    fn configure_apps(&self, cfg: &mut ServiceConfig) {
+        cfg.app_data(self.inner.events.clone())
+            .app_data(self.clone());
+        if let Some(health) = &self.inner.health {
+            health.configure(cfg);
+        }
+        self.inner.metrics.configure(cfg);
+        configure_web(
+            ordered(&self.inner.apps, &self.inner.order),
+            &self.inner.metrics,
+            cfg,
+        );
+    }
+
+    fn middleware(&self) -> ReactorMiddleware {
+        FrozenReactor::middleware(self)
+    }
+}
+
+impl ConfigureApps for WeakReactor {
+    /// # Panics
+    ///
+    /// Panics if the reactor is already shut down.
+    fn configure_apps(&self, cfg: &mut ServiceConfig) {
+        self.upgrade()
+            .expect("The reactor is shut down")
+            .configure_apps(cfg)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the reactor is already shut down.
+    fn middleware(&self) -> ReactorMiddleware {
+        self.upgrade()
+            .expect("The reactor is shut down")
+            .middleware()
     }
 }
 
 pub trait ActixReactorExt {
-    fn configure_app(self, r: &Reactor) -> Self;
+    /// Configures the web services of the apps, see [`ConfigureApps`]. The middleware of the apps
+    /// are applied by [`ActixMiddlewareExt::wrap_reactor`].
+    fn configure_app<R>(self, r: &R) -> Self
+    where
+        R: ConfigureApps;
 }
 
 impl<T, B> ActixReactorExt for ActixApp<T, B>
@@ -72,11 +1522,63 @@ where
         InitError = (),
     >,
 {
-    fn configure_app(self, r: &Reactor) -> Self {
-        self.configure(|cfg| {
-            for (_, app) in r.apps.iter() {
-                app.configure_web(cfg);
-            }
-        })
+    fn configure_app<R>(self, r: &R) -> Self
+    where
+        R: ConfigureApps,
+    {
+        self.configure(|cfg| r.configure_apps(cfg))
+    }
+}
+
+pub trait ActixMiddlewareExt {
+    /// Wraps the whole web app in the middleware of the apps and the recorder of the metrics, see
+    /// [`Reactor::middleware`]
+    fn wrap_reactor<R>(
+        self,
+        r: &R,
+    ) -> ActixApp<
+        impl ServiceFactory<
+                Config = (),
+                Request = ServiceRequest,
+                Response = ServiceResponse,
+                Error = Error,
+                InitError = (),
+            > + use<Self, R>,
+        Body,
+    >
+    where
+        R: ConfigureApps;
+}
+
+impl<T, B> ActixMiddlewareExt for ActixApp<T, B>
+where
+    B: MessageBody + Unpin + 'static,
+    T: ServiceFactory<
+        Config = (),
+        Request = ServiceRequest,
+        Response = ServiceResponse<B>,
+        Error = Error,
+        InitError = (),
+    >,
+    T::Service: 'static,
+    <T::Service as Service>::Future: 'static,
+{
+    fn wrap_reactor<R>(
+        self,
+        r: &R,
+    ) -> ActixApp<
+        impl ServiceFactory<
+                Config = (),
+                Request = ServiceRequest,
+                Response = ServiceResponse,
+                Error = Error,
+                InitError = (),
+            > + use<T, B, R>,
+        Body,
+    >
+    where
+        R: ConfigureApps,
+    {
+        self.wrap(r.middleware())
     }
 }
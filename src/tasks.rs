@@ -0,0 +1,266 @@
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_channel::oneshot;
+use futures_util::future::{abortable, AbortHandle, LocalBoxFuture};
+
+use crate::reactor::FrozenReactor;
+
+/// A background task registered by an app, started with the frozen reactor
+pub(crate) type Task = Box<dyn FnOnce(FrozenReactor) -> LocalBoxFuture<'static, ()> + Send>;
+
+struct Running {
+    abort: AbortHandle,
+    // resolves once the task is dropped
+    done: oneshot::Receiver<()>,
+}
+
+/// The background tasks of the apps, started once and cancelled together
+#[derive(Default)]
+pub(crate) struct Tasks {
+    pending: Vec<Task>,
+    running: Vec<Running>,
+}
+
+impl Tasks {
+    pub(crate) fn new(pending: Vec<Task>) -> Self {
+        Self {
+            pending,
+            running: Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_pending(self) -> Vec<Task> {
+        self.pending
+    }
+
+    /// Spawns the tasks which are not started yet on the current arbiter
+    pub(crate) fn start(&mut self, reactor: &FrozenReactor) {
+        for task in self.pending.drain(..) {
+            let (done_tx, done) = oneshot::channel::<()>();
+            let (task, abort) = abortable(task(reactor.clone()));
+            actix_rt::spawn(async move {
+                // dropped after the task, so it's dropped when `done` resolves
+                let _done = done_tx;
+                let _ = task.await;
+            });
+            self.running.push(Running { abort, done });
+        }
+    }
+
+    /// Cancels the running tasks, the returned future resolves once all of them are dropped
+    pub(crate) fn stop(&mut self) -> impl Future<Output = ()> {
+        let running = std::mem::take(&mut self.running);
+        for task in running.iter() {
+            task.abort.abort();
+        }
+        async move {
+            for task in running {
+                let _ = task.done.await;
+            }
+        }
+    }
+}
+
+/// Runs a job in a loop, waiting for the next run of the schedule before each run
+pub(crate) fn scheduled<F, Fut>(schedule: Schedule, job: F) -> Task
+where
+    F: Fn(FrozenReactor) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    Box::new(move |reactor| {
+        Box::pin(async move {
+            while let Some(delay) = schedule.delay(SystemTime::now()) {
+                actix_rt::time::delay_for(delay).await;
+                job(reactor.clone()).await;
+            }
+        })
+    })
+}
+
+/// When a scheduled job runs, see [`Registry::schedule`](crate::Registry::schedule)
+#[derive(Clone)]
+pub struct Schedule {
+    kind: Kind,
+}
+
+#[derive(Clone)]
+enum Kind {
+    Every(Duration),
+    Cron(Box<Cron>),
+}
+
+impl Schedule {
+    /// Runs the job every `period`, the first run is one period after the task is started.
+    ///
+    /// The period is counted from the end of the previous run, so runs never overlap.
+    pub fn every(period: Duration) -> Self {
+        Self {
+            kind: Kind::Every(period),
+        }
+    }
+
+    /// Runs the job at the times matching a cron expression, in UTC.
+    ///
+    /// The expression has the five usual fields, minute, hour, day of month, month and day of
+    /// week, each one a `*`, a number, a range like `1-5`, or a list of them, with an optional
+    /// step like `*/15`, and `5/15` stands for `5-59/15`. Sunday is both `0` and `7`.
+    pub fn cron(expr: &str) -> Result<Self, String> {
+        let fields: Vec<_> = expr.split_whitespace().collect();
+        let (minutes, hours, days, months, weekdays) = match fields[..] {
+            [minutes, hours, days, months, weekdays] => (minutes, hours, days, months, weekdays),
+            _ => {
+                return Err(format!(
+                    "`{}` should have 5 fields, found {}",
+                    expr,
+                    fields.len()
+                ))
+            }
+        };
+
+        let sundays = parse_field(weekdays, 0, 7)?;
+        let cron = Cron {
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days: parse_field(days, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            // 7 is another name for sunday
+            weekdays: (sundays | sundays >> 7) & 0x7f,
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        };
+        Ok(Self {
+            kind: Kind::Cron(Box::new(cron)),
+        })
+    }
+
+    /// The next time the job runs after `after`, `None` if a cron expression never matches
+    pub fn next(&self, after: SystemTime) -> Option<SystemTime> {
+        match &self.kind {
+            Kind::Every(period) => Some(after + *period),
+            Kind::Cron(cron) => {
+                let secs = after.duration_since(UNIX_EPOCH).ok()?.as_secs();
+                let next = cron.next_minute(secs / 60 + 1)?;
+                Some(UNIX_EPOCH + Duration::from_secs(next * 60))
+            }
+        }
+    }
+
+    fn delay(&self, now: SystemTime) -> Option<Duration> {
+        let next = self.next(now)?;
+        Some(next.duration_since(now).unwrap_or_default())
+    }
+}
+
+impl fmt::Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            Kind::Every(period) => write!(f, "Schedule::every({:?})", period),
+            Kind::Cron(_) => f.write_str("Schedule::cron(..)"),
+        }
+    }
+}
+
+/// The values matching each field of a cron expression, as bit sets
+#[derive(Clone)]
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    // when both the day of month and the day of week are restricted, matching either is enough
+    any_day: bool,
+    any_weekday: bool,
+}
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+
+impl Cron {
+    fn matches_day(&self, days: u64) -> bool {
+        let (month, day) = month_day(days);
+        if !has(self.months, month) {
+            return false;
+        }
+        // 1970-01-01 was a thursday
+        let weekday = has(self.weekdays, (days + 4) % 7);
+        match (self.any_day, self.any_weekday) {
+            (false, false) => has(self.days, day) || weekday,
+            _ => has(self.days, day) && weekday,
+        }
+    }
+
+    /// The first matching minute since the epoch, starting from `minute`
+    fn next_minute(&self, mut minute: u64) -> Option<u64> {
+        // leap days come back within 8 years
+        let limit = minute + 8 * 366 * MINUTES_PER_DAY;
+        while minute < limit {
+            let days = minute / MINUTES_PER_DAY;
+            if !self.matches_day(days) {
+                minute = (days + 1) * MINUTES_PER_DAY;
+            } else if !has(self.hours, minute / 60 % 24) {
+                minute = (minute / 60 + 1) * 60;
+            } else if !has(self.minutes, minute % 60) {
+                minute += 1;
+            } else {
+                return Some(minute);
+            }
+        }
+        None
+    }
+}
+
+fn has(set: u64, value: u64) -> bool {
+    set & (1 << value) != 0
+}
+
+/// The month and the day of a day since the epoch
+fn month_day(days: u64) -> (u64, u64) {
+    // from the `civil_from_days` algorithm by Howard Hinnant
+    let z = days + 719_468;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (month, day)
+}
+
+fn parse_field(field: &str, min: u64, max: u64) -> Result<u64, String> {
+    let number = |value: &str| -> Result<u64, String> {
+        match value.parse() {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(format!(
+                "`{}` should be a number from {} to {}",
+                value, min, max
+            )),
+        }
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(idx) => match part[idx + 1..].parse() {
+                Ok(step) if step > 0 => (&part[..idx], Some(step)),
+                _ => return Err(format!("invalid step in `{}`", part)),
+            },
+            None => (part, None),
+        };
+        let (start, end) = match range.find('-') {
+            _ if range == "*" => (min, max),
+            Some(idx) => (number(&range[..idx])?, number(&range[idx + 1..])?),
+            // `5/10` starts at 5 and goes to the end, whatever the step
+            None if step.is_some() => (number(range)?, max),
+            None => (number(range)?, number(range)?),
+        };
+        if start > end {
+            return Err(format!("invalid range `{}`", range));
+        }
+        for value in (start..=end).step_by(step.unwrap_or(1)) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
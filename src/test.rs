@@ -0,0 +1,142 @@
+//! Helpers for testing apps in the context of a full project
+//!
+//! ```ignore
+//! #[actix_rt::test]
+//! async fn test_login() {
+//!     let reactor = Reactor::shared().add(AuthApp::default());
+//!     let mut app = TestApp::new(reactor).await;
+//!
+//!     let res = app.get("/auth/admin/admin").await;
+//!     let body: ApiResponse<User> = read_response(res).await;
+//! }
+//! ```
+
+use actix_http::Request;
+use actix_service::boxed::{self, BoxService};
+use actix_web::dev::ServiceResponse;
+use actix_web::error::Error;
+use actix_web::test::{self, TestRequest};
+use actix_web::App as ActixApp;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::reactor::{ActixMiddlewareExt, ActixReactorExt, FrozenReactor, SharedReactor};
+
+/// An actix test service configured with the apps of a reactor
+pub struct TestApp {
+    service: BoxService<Request, ServiceResponse, Error>,
+    reactor: FrozenReactor,
+}
+
+impl TestApp {
+    /// Builds the reactor using [`Reactor::build_async`] and initializes a test service with its
+    /// apps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the reactor fails to build.
+    pub async fn new(reactor: SharedReactor) -> Self {
+        Self::with_frozen(reactor.build_async().await.freeze()).await
+    }
+
+    /// Initializes a test service with the apps of a built reactor, wrapped in their middleware
+    pub async fn with_frozen(reactor: FrozenReactor) -> Self {
+        let app = ActixApp::new()
+            .configure_app(&reactor)
+            .wrap_reactor(&reactor);
+        let service = test::init_service(app).await;
+        Self {
+            service: boxed::service(service),
+            reactor,
+        }
+    }
+
+    /// The reactor the apps are built by
+    pub fn reactor(&self) -> &FrozenReactor {
+        &self.reactor
+    }
+
+    /// Calls the service with a request, the errors of the handlers are turned into responses
+    pub async fn call(&mut self, req: TestRequest) -> ServiceResponse {
+        test::call_service(&mut self.service, req.to_request()).await
+    }
+
+    pub async fn get(&mut self, path: &str) -> ServiceResponse {
+        self.call(TestRequest::get().uri(path)).await
+    }
+
+    pub async fn delete(&mut self, path: &str) -> ServiceResponse {
+        self.call(TestRequest::delete().uri(path)).await
+    }
+
+    pub async fn post<T>(&mut self, path: &str, body: &T) -> ServiceResponse
+    where
+        T: Serialize,
+    {
+        self.call(TestRequest::post().uri(path).set_json(body))
+            .await
+    }
+
+    pub async fn put<T>(&mut self, path: &str, body: &T) -> ServiceResponse
+    where
+        T: Serialize,
+    {
+        self.call(TestRequest::put().uri(path).set_json(body)).await
+    }
+}
+
+/// The body of a `yeax_api::JsonResponse`
+#[derive(Debug, Deserialize)]
+pub struct ApiResponse<T> {
+    pub status: u16,
+    /// `None` for the responses without a content
+    pub content: Option<T>,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+    pub total: Option<usize>,
+}
+
+/// The body of a `yeax_api::JsonError`
+#[derive(Debug, Deserialize)]
+pub struct ApiError<T = serde_json::Value> {
+    pub status: u16,
+    pub code: String,
+    pub hint: Option<String>,
+    /// `None` for the errors without a content
+    pub content: Option<T>,
+}
+
+/// Reads the body of a response as JSON
+///
+/// # Panics
+///
+/// Panics showing the body if it can't be deserialized.
+pub async fn read_json<T>(res: ServiceResponse) -> T
+where
+    T: DeserializeOwned,
+{
+    let body = test::read_body(res).await;
+    serde_json::from_slice(&body).unwrap_or_else(|err| {
+        panic!(
+            "Failed to deserialize the response `{}`: {}",
+            String::from_utf8_lossy(&body),
+            err
+        )
+    })
+}
+
+/// Reads the body of a `yeax_api::JsonResponse`
+pub async fn read_response<T>(res: ServiceResponse) -> ApiResponse<T>
+where
+    T: DeserializeOwned,
+{
+    read_json(res).await
+}
+
+/// Reads the body of a `yeax_api::JsonError`
+pub async fn read_error<T>(res: ServiceResponse) -> ApiError<T>
+where
+    T: DeserializeOwned,
+{
+    read_json(res).await
+}
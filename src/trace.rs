@@ -0,0 +1,153 @@
+//! Spans attributing the lifecycle phases, the DI hooks and the requests to the apps, recorded
+//! with the `tracing` feature and compiled away without it
+
+#[cfg(feature = "tracing")]
+pub(crate) use self::spans::*;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use self::noop::*;
+
+#[cfg(feature = "tracing")]
+mod spans {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use actix_web::dev::{ServiceRequest, ServiceResponse};
+    use actix_web::error::Error;
+    use actix_web::HttpMessage;
+    use futures_util::future::LocalBoxFuture;
+    use tracing::field::Empty;
+    pub(crate) use tracing::{Instrument, Span};
+
+    use crate::app::AppId;
+    use crate::middleware::{Middleware, Next};
+
+    /// A lifecycle phase of an app, like `init`
+    pub(crate) fn phase(phase: &'static str, app: AppId) -> Span {
+        tracing::info_span!(
+            "yeax::phase",
+            phase,
+            app = app.name(),
+            instance = app.instance()
+        )
+    }
+
+    /// A DI hook, named by its parameters like `(&mut Auth, Option<&Mail>)`
+    pub(crate) fn inject(owner: &'static str, hook: &str) -> Span {
+        tracing::info_span!("yeax::inject", app = owner, params = hook)
+    }
+
+    /// Whether the requests are attributed to the apps handling them
+    pub(crate) const REQUESTS: bool = true;
+
+    /// The middleware opening a span for each request handled by an app, `root` for the apps
+    /// without a mount point
+    pub(crate) fn requests(app: AppId, root: bool) -> Option<Arc<dyn Middleware>> {
+        Some(Arc::new(Traced { app, root }))
+    }
+
+    struct Traced {
+        app: AppId,
+        root: bool,
+    }
+
+    /// The span of a request and whether an inner root app claimed it, the scopes of the root
+    /// apps are nested so the outer ones see the requests of the inner ones too
+    struct Request {
+        span: Span,
+        claimed: Rc<Cell<bool>>,
+    }
+
+    impl Middleware for Traced {
+        fn call(
+            &self,
+            req: ServiceRequest,
+            next: Next,
+        ) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>> {
+            let outer = req.extensions().get::<Request>().map(|outer| {
+                outer.claimed.set(true);
+                outer.span.clone()
+            });
+            // a root app is only known to handle the request once it's routed to the nested scopes
+            let app = Some(self.app).filter(|_| !self.root);
+            let span = match &outer {
+                Some(span) => span.clone(),
+                None => tracing::info_span!(
+                    "yeax::request",
+                    app = app.map(|app| app.name()),
+                    instance = app.and_then(|app| app.instance()),
+                    method = %req.method(),
+                    path = req.path(),
+                    status = Empty,
+                ),
+            };
+            let claimed = Rc::new(Cell::new(false));
+            req.extensions_mut().insert(Request {
+                span: span.clone(),
+                claimed: claimed.clone(),
+            });
+
+            let res = span.in_scope(|| next.call(req));
+            if self.root && !claimed.get() {
+                span.record("app", self.app.name());
+                if let Some(instance) = self.app.instance() {
+                    span.record("instance", instance);
+                }
+            }
+            if outer.is_some() {
+                // instrumented by the outer app
+                return res;
+            }
+            let instrumented = span.clone();
+            Box::pin(
+                async move {
+                    let res = res.await;
+                    if let Ok(res) = &res {
+                        span.record("status", res.status().as_u16());
+                    }
+                    res
+                }
+                .instrument(instrumented),
+            )
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod noop {
+    use std::sync::Arc;
+
+    use crate::app::AppId;
+    use crate::middleware::Middleware;
+
+    pub(crate) struct Span;
+
+    impl Span {
+        pub(crate) fn entered(self) -> Self {
+            self
+        }
+    }
+
+    pub(crate) trait Instrument: Sized {
+        fn instrument(self, _: Span) -> Self {
+            self
+        }
+    }
+
+    impl<T> Instrument for T {}
+
+    pub(crate) fn phase(_: &'static str, _: AppId) -> Span {
+        Span
+    }
+
+    pub(crate) fn inject(_: &'static str, _: &str) -> Span {
+        Span
+    }
+
+    pub(crate) const REQUESTS: bool = false;
+
+    pub(crate) fn requests(_: AppId, _: bool) -> Option<Arc<dyn Middleware>> {
+        None
+    }
+}
@@ -1,6 +1,11 @@
 //! Tests are not complete yet
 
-use yeax::{App, Reactor, Registry};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use actix_web::web::ServiceConfig;
+use yeax::{ActixReactorExt, App, AppId, BuildError, InstanceName, Named, Reactor, Registry};
 
 struct A {
     a: i32,
@@ -44,3 +49,705 @@ fn test_it() {
     let d = D { d: 10 };
     let _ = Reactor::default().add(a).add(b).add(c).add(d).build();
 }
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+type Log = Rc<RefCell<Vec<String>>>;
+
+struct Lifecycle {
+    name: &'static str,
+    log: Log,
+}
+
+impl Lifecycle {
+    fn new(name: &'static str, log: &Log) -> Self {
+        Self {
+            name,
+            log: log.clone(),
+        }
+    }
+
+    fn record(&self, phase: &str) {
+        self.log
+            .borrow_mut()
+            .push(format!("{}:{}", phase, self.name));
+    }
+}
+
+impl App for Lifecycle {
+    fn pre_init(&mut self) {
+        self.record("pre_init")
+    }
+
+    fn init(&mut self, r: &mut Registry) {
+        self.record("init");
+        r.register_di(|me: &mut Lifecycle| me.record("di"));
+    }
+
+    fn post_init(&mut self) {
+        self.record("post_init")
+    }
+
+    fn configure_web(&self, _: &mut ServiceConfig) {
+        self.record("configure_web")
+    }
+
+    fn finish(&mut self) {
+        self.record("finish")
+    }
+}
+
+struct Other(Lifecycle);
+
+impl App for Other {
+    fn pre_init(&mut self) {
+        self.0.pre_init()
+    }
+
+    fn init(&mut self, _: &mut Registry) {
+        self.0.record("init")
+    }
+
+    fn post_init(&mut self) {
+        self.0.post_init()
+    }
+
+    fn finish(&mut self) {
+        self.0.finish()
+    }
+}
+
+fn phases(log: &Log, phase: &str) -> usize {
+    log.borrow()
+        .iter()
+        .filter(|entry| entry.starts_with(&format!("{}:", phase)))
+        .count()
+}
+
+fn position(log: &Log, entry: &str) -> usize {
+    log.borrow().iter().position(|e| e == entry).unwrap()
+}
+
+#[test]
+fn test_pre_init() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Lifecycle::new("first", &log))
+        .add(Other(Lifecycle::new("second", &log)))
+        .build();
+
+    assert_eq!(phases(&log, "pre_init"), 2);
+    // every app is pre-initialized before any app is initialized
+    assert!(position(&log, "pre_init:first") < position(&log, "init:second"));
+    assert!(position(&log, "pre_init:second") < position(&log, "init:first"));
+}
+
+#[test]
+fn test_init() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Lifecycle::new("first", &log))
+        .add(Other(Lifecycle::new("second", &log)))
+        .build();
+
+    assert_eq!(phases(&log, "init"), 2);
+}
+
+#[test]
+fn test_di_hooks_after_init() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Lifecycle::new("first", &log))
+        .add(Other(Lifecycle::new("second", &log)))
+        .build();
+
+    assert_eq!(phases(&log, "di"), 1);
+    assert!(position(&log, "init:first") < position(&log, "di:first"));
+    assert!(position(&log, "init:second") < position(&log, "di:first"));
+}
+
+#[test]
+fn test_post_init() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Lifecycle::new("first", &log))
+        .add(Other(Lifecycle::new("second", &log)))
+        .build();
+
+    assert_eq!(phases(&log, "post_init"), 2);
+    // all the hooks are run before any app is post-initialized
+    assert!(position(&log, "di:first") < position(&log, "post_init:first"));
+    assert!(position(&log, "di:first") < position(&log, "post_init:second"));
+}
+
+#[test]
+fn test_finish() {
+    let log = Log::default();
+    let reactor = Reactor::default()
+        .add(Lifecycle::new("first", &log))
+        .add(Other(Lifecycle::new("second", &log)))
+        .build();
+
+    assert_eq!(phases(&log, "finish"), 0);
+    reactor.finish();
+    assert_eq!(phases(&log, "finish"), 2);
+}
+
+/// An app which can be shared between the workers, recording its phases
+struct Shared(Arc<Mutex<Vec<&'static str>>>);
+
+impl Shared {
+    fn count(&self, phase: &str) -> usize {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| **p == phase)
+            .count()
+    }
+}
+
+impl App for Shared {
+    fn init(&mut self, _: &mut Registry) {
+        self.0.lock().unwrap().push("init")
+    }
+
+    fn configure_web(&self, _: &mut ServiceConfig) {
+        self.0.lock().unwrap().push("configure_web")
+    }
+
+    fn finish(&mut self) {
+        self.0.lock().unwrap().push("finish")
+    }
+}
+
+#[test]
+fn test_frozen_reactor_shared_between_workers() {
+    let phases = Shared(Default::default());
+    let reactor = Reactor::shared()
+        .add(Shared(phases.0.clone()))
+        .build()
+        .freeze();
+
+    let workers: Vec<_> = (0..2)
+        .map(|_| {
+            let reactor = reactor.clone();
+            std::thread::spawn(move || {
+                let _ = actix_web::App::new().configure_app(&reactor);
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    // initialized once, configured by every worker
+    assert_eq!(phases.count("init"), 1);
+    assert_eq!(phases.count("configure_web"), 2);
+
+    let clone = reactor.clone();
+    let reactor = match reactor.unfreeze() {
+        Ok(_) => panic!("The reactor is still shared"),
+        Err(reactor) => reactor,
+    };
+    drop(clone);
+    match reactor.unfreeze() {
+        Ok(reactor) => reactor.finish(),
+        Err(_) => panic!("The reactor is not shared anymore"),
+    }
+    assert_eq!(phases.count("finish"), 1);
+}
+
+struct Dependent(Lifecycle);
+
+impl App for Dependent {
+    fn init(&mut self, r: &mut Registry) {
+        self.0.record("init");
+        r.register_di(|_: &mut Dependent, _: &mut Lifecycle| {});
+    }
+
+    fn post_init(&mut self) {
+        self.0.post_init()
+    }
+}
+
+#[test]
+fn test_insertion_order() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Other(Lifecycle::new("second", &log)))
+        .add(Lifecycle::new("first", &log))
+        .build();
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "pre_init:second",
+            "pre_init:first",
+            "init:second",
+            "init:first",
+            "di:first",
+            "post_init:second",
+            "post_init:first",
+        ]
+    );
+}
+
+#[test]
+fn test_dependency_order() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Dependent(Lifecycle::new("dependent", &log)))
+        .add(Lifecycle::new("first", &log))
+        .build();
+
+    // apps are initialized in insertion order, but post-initialized after their dependencies
+    assert!(position(&log, "init:dependent") < position(&log, "init:first"));
+    assert!(position(&log, "post_init:first") < position(&log, "post_init:dependent"));
+}
+
+struct Declared(Lifecycle);
+
+impl App for Declared {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Lifecycle>()]
+    }
+
+    fn pre_init(&mut self) {
+        self.0.pre_init()
+    }
+
+    fn init(&mut self, _: &mut Registry) {
+        self.0.record("init")
+    }
+
+    fn post_init(&mut self) {
+        self.0.post_init()
+    }
+
+    fn configure_web(&self, cfg: &mut ServiceConfig) {
+        self.0.configure_web(cfg)
+    }
+}
+
+#[test]
+fn test_declared_dependency_order() {
+    let log = Log::default();
+    let reactor = Reactor::default()
+        .add(Declared(Lifecycle::new("declared", &log)))
+        .add(Lifecycle::new("first", &log))
+        .build();
+    let _ = actix_web::App::new().configure_app(&reactor);
+
+    // every phase runs after the declared dependencies, including `init`
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "pre_init:first",
+            "pre_init:declared",
+            "init:first",
+            "init:declared",
+            "di:first",
+            "post_init:first",
+            "post_init:declared",
+            "configure_web:first",
+            "configure_web:declared",
+        ]
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "`test_apps::Declared` depends on `test_apps::Lifecycle` which is not registered"
+)]
+fn test_missing_dependency() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Declared(Lifecycle::new("declared", &log)))
+        .build();
+}
+
+struct Cyclic<T>(Lifecycle, T);
+
+impl<T: 'static> App for Cyclic<T> {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Cyclic<bool>>(), AppId::of::<Cyclic<u8>>()]
+    }
+
+    fn pre_init(&mut self) {
+        self.0.pre_init()
+    }
+}
+
+#[test]
+fn test_dependency_cycle() {
+    let log = Log::default();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        Reactor::default()
+            .add(Cyclic(Lifecycle::new("bool", &log), true))
+            .add(Cyclic(Lifecycle::new("u8", &log), 0u8))
+            .build()
+    }));
+
+    let err = result.err().unwrap();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert!(message.contains(
+        "dependency cycle: test_apps::Cyclic<bool> -> test_apps::Cyclic<u8> -> test_apps::Cyclic<bool>"
+    ));
+    // no hook is run when the dependencies are broken
+    assert!(log.borrow().is_empty());
+}
+
+struct Broken;
+
+impl App for Broken {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|_: &mut Broken, _: &mut A| {});
+        r.register_di(|_: &mut Broken, _: &mut B, _: &mut Broken| {});
+    }
+}
+
+#[test]
+fn test_try_build_reports_all_params() {
+    let err = Reactor::default()
+        .add(Broken)
+        .add(Declared(Lifecycle::new("declared", &Log::default())))
+        .add(Lifecycle::new("first", &Log::default()))
+        .try_build()
+        .err()
+        .unwrap();
+
+    let errors = err.errors();
+    assert_eq!(errors.len(), 2);
+    // the second hook is rejected when registered, so its other params are not checked
+    assert!(matches!(
+        errors[0],
+        BuildError::DuplicateParam {
+            param: "test_apps::Broken",
+            ..
+        }
+    ));
+    assert!(matches!(
+        errors[1],
+        BuildError::MissingParam {
+            param: "test_apps::A",
+            ..
+        }
+    ));
+    assert_eq!(
+        errors[1].to_string(),
+        "hook `(&mut test_apps::Broken, &mut test_apps::A)` of `test_apps::Broken` depends on \
+         `test_apps::A` which is not registered"
+    );
+}
+
+#[test]
+fn test_try_build_reports_failed_hooks() {
+    struct Failing(Lifecycle);
+
+    impl App for Failing {
+        fn init(&mut self, r: &mut Registry) {
+            r.register_di(|_: &mut Failing| Err("first"));
+            r.register_di(|me: &mut Failing| -> Result<(), String> {
+                me.0.record("di");
+                Ok(())
+            });
+            r.register_di(|_: &mut Failing| Err("second"));
+        }
+
+        fn post_init(&mut self) {
+            self.0.post_init()
+        }
+    }
+
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(Failing(Lifecycle::new("failing", &log)))
+        .try_build()
+        .err()
+        .unwrap();
+
+    let message = err.to_string();
+    assert!(message
+        .contains("of `test_apps::test_try_build_reports_failed_hooks::Failing` failed: first"));
+    assert!(message.contains("failed: second"));
+    // the hooks after the failed one are still run, but the apps are not post-initialized
+    assert_eq!(*log.borrow(), vec!["di:failing"]);
+}
+
+struct Integration(Log);
+
+impl App for Integration {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|me: &mut Integration, first: Option<&mut Lifecycle>| {
+            let entry = match first {
+                Some(first) => format!("integrated:{}", first.name),
+                None => "integrated:none".to_string(),
+            };
+            me.0.borrow_mut().push(entry);
+        });
+    }
+}
+
+#[test]
+fn test_optional_dependency_present() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Integration(log.clone()))
+        .add(Lifecycle::new("first", &log))
+        .build();
+
+    assert_eq!(phases(&log, "integrated"), 1);
+    assert!(log.borrow().contains(&"integrated:first".to_string()));
+}
+
+#[test]
+fn test_optional_dependency_missing() {
+    let log = Log::default();
+    let _ = Reactor::default().add(Integration(log.clone())).build();
+
+    assert_eq!(*log.borrow(), vec!["integrated:none"]);
+}
+
+#[test]
+fn test_optional_dependency_used_twice() {
+    struct Twice;
+
+    impl App for Twice {
+        fn init(&mut self, r: &mut Registry) {
+            r.register_di(|_: Option<&mut Twice>, _: &mut Twice| {});
+        }
+    }
+
+    let err = Reactor::default().add(Twice).try_build().err().unwrap();
+    assert!(matches!(err.errors(), [BuildError::DuplicateParam { .. }]));
+}
+
+#[test]
+fn test_conflicts_reported_once() {
+    struct Conflicts;
+
+    impl App for Conflicts {
+        fn init(&mut self, r: &mut Registry) {
+            // the mutable borrow comes after the shared ones
+            r.register_di(|_: &Lifecycle, _: &Lifecycle, _: &mut Lifecycle| {});
+            // the mutable borrow conflicts with both shared ones
+            r.register_di(|_: &mut Other, _: &Other, _: &Other| {});
+        }
+    }
+
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(Conflicts)
+        .add(Lifecycle::new("first", &log))
+        .add(Other(Lifecycle::new("other", &log)))
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [
+            BuildError::DuplicateParam {
+                param: "test_apps::Lifecycle",
+                ..
+            },
+            BuildError::DuplicateParam {
+                param: "test_apps::Other",
+                ..
+            },
+        ]
+    ));
+}
+
+struct Reader(Log);
+
+impl App for Reader {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|me: &Reader, first: &Lifecycle, again: &Lifecycle| {
+            let entry = format!("read:{}:{}", first.name, again.name);
+            me.0.borrow_mut().push(entry);
+        });
+        r.register_di(|me: &mut Reader, first: Option<&Lifecycle>| {
+            let entry = format!("read:{}", first.map_or("none", |first| first.name));
+            me.0.borrow_mut().push(entry);
+        });
+    }
+}
+
+#[test]
+fn test_shared_dependencies() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add(Reader(log.clone()))
+        .add(Lifecycle::new("first", &log))
+        .build();
+
+    assert!(log.borrow().contains(&"read:first:first".to_string()));
+    assert!(log.borrow().contains(&"read:first".to_string()));
+}
+
+#[test]
+fn test_shared_and_mutable_conflict() {
+    struct Conflict;
+
+    impl App for Conflict {
+        fn init(&mut self, r: &mut Registry) {
+            r.register_di(|_: &Lifecycle, _: &mut Lifecycle| {});
+            r.register_di(|_: &mut Lifecycle, _: Option<&Lifecycle>| {});
+        }
+    }
+
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(Conflict)
+        .add(Lifecycle::new("first", &log))
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert_eq!(err.errors().len(), 2);
+    assert!(err
+        .errors()
+        .iter()
+        .all(|err| matches!(err, BuildError::DuplicateParam { .. })));
+    // conflicts are found when registering, so no hook is run
+    assert!(!log.borrow().iter().any(|entry| entry.starts_with("di:")));
+}
+
+struct Realm {
+    label: &'static str,
+    log: Log,
+    users: Vec<&'static str>,
+}
+
+impl Realm {
+    fn new(label: &'static str, log: &Log) -> Self {
+        Self {
+            label,
+            log: log.clone(),
+            users: Vec::new(),
+        }
+    }
+}
+
+impl App for Realm {
+    fn post_init(&mut self) {
+        let entry = format!("{}:{}", self.label, self.users.join(","));
+        self.log.borrow_mut().push(entry);
+    }
+}
+
+struct Staff;
+
+impl InstanceName for Staff {
+    const NAME: &'static str = "staff";
+}
+
+struct Customers;
+
+impl InstanceName for Customers {
+    const NAME: &'static str = "customers";
+}
+
+struct Admin;
+
+impl App for Admin {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(
+            |mut staff: Named<Staff, &mut Realm>,
+             customers: Named<Customers, &mut Realm>,
+             default: Option<&Realm>| {
+                staff.users.push("admin");
+                customers.into_inner().users.push("guest");
+                assert!(default.is_none());
+            },
+        );
+    }
+}
+
+#[test]
+fn test_named_instances() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add_named("staff", Realm::new("staff", &log))
+        .add_named("customers", Realm::new("customers", &log))
+        .add(Admin)
+        .build();
+
+    let log = log.borrow();
+    assert!(log.contains(&"staff:admin".to_string()));
+    assert!(log.contains(&"customers:guest".to_string()));
+}
+
+#[test]
+fn test_named_instances_replaced_by_name() {
+    let log = Log::default();
+    let _ = Reactor::default()
+        .add_named("staff", Realm::new("staff", &log))
+        .add_named("customers", Realm::new("old", &log))
+        .add_named("customers", Realm::new("customers", &log))
+        .add(Realm::new("default", &log))
+        .build();
+
+    assert_eq!(*log.borrow(), vec!["staff:", "customers:", "default:"]);
+}
+
+#[test]
+fn test_missing_named_instance() {
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(Realm::new("default", &log))
+        .add_named("staff", Realm::new("staff", &log))
+        .add(Admin)
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [BuildError::MissingParam {
+            app: "test_apps::Admin",
+            param: "test_apps::Realm",
+            instance: Some("customers"),
+            ..
+        }]
+    ));
+    assert!(err.errors()[0]
+        .to_string()
+        .contains("depends on the `customers` instance of `test_apps::Realm`"));
+}
+
+#[test]
+fn test_named_dependencies() {
+    struct Reports;
+
+    impl App for Reports {
+        fn dependencies(&self) -> Vec<AppId> {
+            vec![AppId::named::<Realm>("staff")]
+        }
+    }
+
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(Realm::new("default", &log))
+        .add(Reports)
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.errors(),
+        [BuildError::MissingApp {
+            instance: Some("staff"),
+            ..
+        }]
+    ));
+    assert_eq!(
+        err.errors()[0].to_string(),
+        "`test_apps::test_named_dependencies::Reports` depends on the `staff` instance of `test_apps::Realm` which is not registered"
+    );
+
+    let _ = Reactor::default()
+        .add(Reports)
+        .add_named("staff", Realm::new("staff", &log))
+        .build();
+}
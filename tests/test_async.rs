@@ -0,0 +1,175 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use actix_rt::time::delay_for;
+use futures_util::future::LocalBoxFuture;
+use yeax::{App, BuildError, Reactor, Registry};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+struct Pool {
+    log: Log,
+    connections: usize,
+}
+
+impl App for Pool {
+    fn init_async<'a>(&'a mut self, r: &'a mut Registry) -> LocalBoxFuture<'a, ()> {
+        Box::pin(async move {
+            // opening the connections
+            delay_for(Duration::from_millis(1)).await;
+            self.connections = 4;
+            self.log.borrow_mut().push("init".to_string());
+
+            r.register_di_async(migrate);
+        })
+    }
+
+    fn post_init_async(&mut self) -> LocalBoxFuture<'_, ()> {
+        Box::pin(async move {
+            delay_for(Duration::from_millis(1)).await;
+            self.log.borrow_mut().push("post_init".to_string());
+        })
+    }
+
+    fn finish_async(&mut self) -> LocalBoxFuture<'_, ()> {
+        Box::pin(async move {
+            delay_for(Duration::from_millis(1)).await;
+            self.log.borrow_mut().push("finish".to_string());
+        })
+    }
+}
+
+fn migrate(pool: &mut Pool) -> LocalBoxFuture<'_, ()> {
+    Box::pin(async move {
+        delay_for(Duration::from_millis(1)).await;
+        let entry = format!("migrate:{}", pool.connections);
+        pool.log.borrow_mut().push(entry);
+    })
+}
+
+struct Loader {
+    log: Log,
+}
+
+impl App for Loader {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|loader: &mut Loader| loader.log.borrow_mut().push("di".to_string()));
+        r.register_di_async(load);
+    }
+}
+
+fn load(loader: &mut Loader) -> LocalBoxFuture<'_, ()> {
+    Box::pin(async move { loader.log.borrow_mut().push("load".to_string()) })
+}
+
+struct Users;
+
+impl App for Users {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di_async(
+            |_: &Users, _: &Pool| -> LocalBoxFuture<'static, Result<(), String>> {
+                Box::pin(async { Err("no users table".to_string()) })
+            },
+        );
+    }
+}
+
+struct Cache {
+    log: Log,
+}
+
+impl App for Cache {
+    fn finish(&mut self) {
+        self.log.borrow_mut().push("finish:cache".to_string());
+    }
+}
+
+fn pool(log: &Log) -> Pool {
+    Pool {
+        log: log.clone(),
+        connections: 0,
+    }
+}
+
+#[actix_rt::test]
+async fn test_build_async() {
+    let log = Log::default();
+    let reactor = Reactor::default().add(pool(&log)).build_async().await;
+    reactor.finish_async().await;
+
+    assert_eq!(
+        *log.borrow(),
+        vec!["init", "migrate:4", "post_init", "finish"]
+    );
+}
+
+#[actix_rt::test]
+async fn test_async_hook_errors() {
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(pool(&log))
+        .add(Users)
+        .try_build_async()
+        .await
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [BuildError::HookFailed {
+            app: "test_async::Users",
+            ..
+        }]
+    ));
+}
+
+#[actix_rt::test]
+async fn test_sync_build_reports_waiting_apps() {
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(pool(&log))
+        .add(Users)
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [BuildError::Blocked {
+            app: "test_async::Pool",
+            step: "init"
+        }]
+    ));
+}
+
+#[test]
+fn test_sync_build_rejects_async_hooks() {
+    let log = Log::default();
+    let err = Reactor::default()
+        .add(Loader { log: log.clone() })
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [BuildError::BlockedHook {
+            app: "test_async::Loader",
+            ..
+        }]
+    ));
+    // no hook is run
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn test_sync_finish_of_sync_apps() {
+    let log = Log::default();
+    Reactor::default()
+        .add(Cache { log: log.clone() })
+        .build()
+        .finish();
+
+    assert_eq!(*log.borrow(), vec!["finish:cache"]);
+}
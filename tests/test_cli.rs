@@ -0,0 +1,229 @@
+use std::sync::{Arc, Mutex};
+
+use yeax::{App, Args, CliError, Command, Commands, FrozenReactor, Reactor, SharedReactor};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+struct Auth {
+    log: Log,
+    users: Mutex<Vec<String>>,
+}
+
+impl App for Auth {
+    fn post_init(&mut self) {
+        self.log.lock().unwrap().push("built".to_string());
+    }
+
+    fn finish(&mut self) {
+        self.log.lock().unwrap().push("finished".to_string());
+    }
+
+    fn configure_commands(&self, cmds: &mut Commands) {
+        let log = self.log.clone();
+        let create_user = Command::new("createuser", "Creates a user")
+            .arg("username", "The name of the user")
+            .option("age", "The age of the user")
+            .flag("admin", "Makes the user an admin");
+
+        cmds.add(create_user, move |reactor: FrozenReactor, args: Args| {
+            let log = log.clone();
+            async move {
+                let auth = reactor.get::<Auth>().unwrap();
+                let username = args.get("username").unwrap();
+                let age: Option<u32> = args.parse("age")?;
+                auth.users.lock().unwrap().push(username.to_string());
+
+                let entry = format!("{}:{:?}:{}", username, age, args.flag("admin"));
+                log.lock().unwrap().push(entry);
+                Ok(())
+            }
+        })
+        .add(
+            Command::new("fail", "Always fails"),
+            |_: FrozenReactor, _: Args| async { Err("no reason".to_string()) },
+        );
+    }
+}
+
+struct Seed;
+
+impl App for Seed {
+    fn configure_commands(&self, cmds: &mut Commands) {
+        cmds.add(
+            Command::new("seed", "Loads the fixtures"),
+            |_: FrozenReactor, _: Args| async { Ok(()) },
+        );
+    }
+}
+
+struct Leaky;
+
+impl App for Leaky {
+    fn configure_commands(&self, cmds: &mut Commands) {
+        cmds.add(
+            Command::new("leak", "Keeps the reactor alive"),
+            |reactor: FrozenReactor, _: Args| async move {
+                std::mem::forget(reactor);
+                Ok(())
+            },
+        );
+        cmds.add(
+            Command::new("leakfail", "Keeps the reactor alive and fails"),
+            |reactor: FrozenReactor, _: Args| async move {
+                std::mem::forget(reactor);
+                Err("disk full".to_string())
+            },
+        );
+    }
+}
+
+fn reactor(log: &Log) -> SharedReactor {
+    let auth = Auth {
+        log: log.clone(),
+        users: Mutex::default(),
+    };
+    Reactor::shared().add(auth).add(Seed)
+}
+
+#[actix_rt::test]
+async fn test_run_command() {
+    let log = Log::default();
+    let args = ["manage", "createuser", "--age=30", "admin", "--admin"];
+    let help = reactor(&log).run_cli(args.iter().copied()).await.unwrap();
+
+    assert_eq!(help, None);
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["built", "admin:Some(30):true", "finished"]
+    );
+}
+
+#[actix_rt::test]
+async fn test_invalid_args() {
+    let log = Log::default();
+    for args in [
+        &["manage", "createuser"][..],
+        &["manage", "createuser", "admin", "other"],
+        &["manage", "createuser", "admin", "--age"],
+        &["manage", "createuser", "admin", "--email", "a@b.c"],
+        &["manage", "createuser", "admin", "--admin=yes"],
+    ]
+    .iter()
+    {
+        let err = reactor(&log)
+            .run_cli(args.iter().copied())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CliError::InvalidArgs { .. }), "{}", err);
+    }
+
+    // the apps are finished after the invalid commands too
+    assert_eq!(log.lock().unwrap().len(), 10);
+    assert!(log
+        .lock()
+        .unwrap()
+        .chunks(2)
+        .all(|entries| entries == ["built", "finished"]));
+}
+
+#[actix_rt::test]
+async fn test_failing_command() {
+    let log = Log::default();
+    let err = reactor(&log)
+        .run_cli(vec!["manage", "fail"])
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "command `fail` failed: no reason");
+
+    let err = reactor(&log)
+        .run_cli(vec!["manage", "createuser", "admin", "--age", "old"])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CliError::Failed { .. }));
+
+    let err = reactor(&log)
+        .run_cli(vec!["manage", "migrate"])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CliError::UnknownCommand(command) if command == "migrate"));
+}
+
+#[actix_rt::test]
+async fn test_help() {
+    let log = Log::default();
+    let help = reactor(&log)
+        .run_cli(vec!["manage", "createuser", "--help"])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(help.starts_with("Creates a user\n\nUsage: manage createuser <username>"));
+    assert_eq!(*log.lock().unwrap(), vec!["built", "finished"]);
+
+    let help = reactor(&log)
+        .run_cli(vec!["target/debug/manage"])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        help,
+        "Usage: manage <command> [args]
+
+Commands:
+  test_cli::Auth
+    createuser  Creates a user
+    fail        Always fails
+  test_cli::Seed
+    seed        Loads the fixtures
+
+Run `manage help <command>` for the arguments of a command."
+    );
+}
+
+#[test]
+fn test_duplicate_commands() {
+    let log = Log::default();
+    let err = reactor(&log)
+        .add_named("other", Seed)
+        .build()
+        .cli_help("manage")
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        CliError::DuplicateCommand {
+            apps: ("test_cli::Seed", "test_cli::Seed"),
+            ..
+        }
+    ));
+}
+
+#[actix_rt::test]
+async fn test_unfinished_command() {
+    let err = Reactor::shared()
+        .add(Leaky)
+        .run_cli(vec!["manage", "leak"])
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, CliError::Unfinished { command, result: Ok(()) } if command == "leak"));
+
+    // the failure of the command is kept
+    let err = Reactor::shared()
+        .add(Leaky)
+        .run_cli(vec!["manage", "leakfail"])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "command `leakfail` failed: disk full, and command `leakfail` kept the reactor alive, the \
+         apps are not finished"
+    );
+    assert!(matches!(
+        err,
+        CliError::Unfinished {
+            result: Err(failure),
+            ..
+        } if matches!(*failure, CliError::Failed { .. })
+    ));
+}
@@ -0,0 +1,270 @@
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use yeax::{App, BuildError, Configurable, Reactor, Registry, Settings};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+#[derive(Deserialize)]
+struct AuthConfig {
+    #[serde(default = "default_attempts")]
+    max_attempts: u32,
+    #[serde(default)]
+    realm: String,
+}
+
+fn default_attempts() -> u32 {
+    3
+}
+
+struct Auth {
+    log: Log,
+    config: Option<AuthConfig>,
+}
+
+impl App for Auth {
+    fn init(&mut self, _: &mut Registry) {
+        // the config is handed to the app before it's initialized
+        let entry = match &self.config {
+            Some(config) => format!("{}:{}", config.realm, config.max_attempts),
+            None => "unconfigured".to_string(),
+        };
+        self.log.lock().unwrap().push(entry);
+    }
+}
+
+impl Configurable for Auth {
+    type Config = AuthConfig;
+
+    const NAMESPACE: &'static str = "auth";
+
+    fn configure(&mut self, config: AuthConfig) {
+        self.config = Some(config);
+    }
+}
+
+#[derive(Deserialize)]
+struct MailerConfig {
+    #[allow(dead_code)]
+    host: String,
+}
+
+struct Mailer;
+
+impl App for Mailer {}
+
+impl Configurable for Mailer {
+    type Config = MailerConfig;
+
+    const NAMESPACE: &'static str = "mailer";
+
+    fn configure(&mut self, _: MailerConfig) {}
+}
+
+fn auth(log: &Log) -> Auth {
+    Auth {
+        log: log.clone(),
+        config: None,
+    }
+}
+
+#[test]
+fn test_toml_settings() {
+    let log = Log::default();
+    let settings = Settings::from_toml("[auth]\nrealm = \"admin\"\nmax_attempts = 5\n").unwrap();
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["admin:5"]);
+}
+
+#[test]
+fn test_json_settings() {
+    let log = Log::default();
+    let settings = Settings::from_json(r#"{"auth": {"realm": "users"}}"#).unwrap();
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["users:3"]);
+}
+
+#[test]
+fn test_settings_file() {
+    let path = std::env::temp_dir().join("yeax_test_settings.toml");
+    std::fs::write(&path, "[auth]\nrealm = \"file\"\n").unwrap();
+    let settings = Settings::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let log = Log::default();
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["file:3"]);
+}
+
+#[test]
+fn test_merged_settings() {
+    let dir = std::env::temp_dir();
+    let base = dir.join("yeax_test_base.toml");
+    let local = dir.join("yeax_test_local.json");
+    std::fs::write(&base, "[auth]\nrealm = \"base\"\nmax_attempts = 5\n").unwrap();
+    std::fs::write(&local, r#"{"auth": {"realm": "local"}}"#).unwrap();
+    let settings = Settings::from_file(&base)
+        .unwrap()
+        .merge_file(&local)
+        .unwrap();
+    std::fs::remove_file(&base).unwrap();
+    std::fs::remove_file(&local).unwrap();
+
+    // the tables are merged, keeping the values the later file doesn't override
+    let log = Log::default();
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["local:5"]);
+}
+
+#[test]
+fn test_env_overrides() {
+    std::env::set_var("YEAX_TEST_ENV__AUTH__MAX_ATTEMPTS", "7");
+    std::env::set_var("YEAX_TEST_ENV__AUTH__REALM", "env");
+
+    let log = Log::default();
+    let settings = Settings::from_toml("[auth]\nrealm = \"file\"\nmax_attempts = 5\n")
+        .unwrap()
+        .merge_env("YEAX_TEST_ENV");
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["env:7"]);
+}
+
+#[test]
+fn test_env_strings_stay_strings() {
+    std::env::set_var("YEAX_TEST_STRINGS__AUTH__MAX_ATTEMPTS", "7");
+    std::env::set_var("YEAX_TEST_STRINGS__AUTH__REALM", "12345");
+
+    let log = Log::default();
+    let settings = Settings::default().merge_env("YEAX_TEST_STRINGS");
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["12345:7"]);
+}
+
+#[test]
+fn test_named_instances_read_their_own_table() {
+    let log = Log::default();
+    let settings = Settings::from_toml(
+        "[auth]\nrealm = \"users\"\n[auth.staff]\nrealm = \"staff\"\nmax_attempts = 1\n",
+    )
+    .unwrap();
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .add_configured_named("staff", auth(&log))
+        .build();
+
+    let mut log = log.lock().unwrap().clone();
+    log.sort();
+    assert_eq!(log, vec!["staff:1", "users:3"]);
+}
+
+#[test]
+fn test_named_tables_are_left_out_of_the_unnamed_config() {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct StrictConfig {
+        #[allow(dead_code)]
+        realm: String,
+    }
+
+    struct Strict;
+
+    impl App for Strict {}
+
+    impl Configurable for Strict {
+        type Config = StrictConfig;
+
+        const NAMESPACE: &'static str = "strict";
+
+        fn configure(&mut self, _: StrictConfig) {}
+    }
+
+    let settings =
+        Settings::from_toml("[strict]\nrealm = \"users\"\n[strict.staff]\nrealm = \"staff\"\n")
+            .unwrap();
+    let reactor = Reactor::default()
+        .with_settings(settings)
+        .add_configured(Strict)
+        .add_configured_named("staff", Strict);
+
+    assert!(reactor.try_build().is_ok());
+}
+
+#[test]
+fn test_missing_section_uses_defaults() {
+    let log = Log::default();
+    let _ = Reactor::default().add_configured(auth(&log)).build();
+
+    assert_eq!(*log.lock().unwrap(), vec![":3"]);
+}
+
+#[test]
+fn test_unconfigured_apps_are_untouched() {
+    let log = Log::default();
+    let settings = Settings::from_toml("[auth]\nrealm = \"admin\"\n").unwrap();
+    let _ = Reactor::default()
+        .with_settings(settings)
+        .add(auth(&log))
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["unconfigured"]);
+}
+
+#[test]
+fn test_invalid_configs_are_reported_before_init() {
+    let log = Log::default();
+    let settings = Settings::from_toml("[auth]\nmax_attempts = \"many\"\n").unwrap();
+    let err = Reactor::default()
+        .with_settings(settings)
+        .add_configured(auth(&log))
+        .add_configured(Mailer)
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [
+            BuildError::InvalidConfig {
+                app: "test_config::Auth",
+                ..
+            },
+            BuildError::InvalidConfig {
+                app: "test_config::Mailer",
+                ..
+            }
+        ]
+    ));
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_invalid_settings() {
+    assert!(Settings::from_toml("auth = ").is_err());
+    assert!(Settings::from_json("[1, 2]").is_err());
+    assert!(Settings::from_file("settings.yaml").is_err());
+}
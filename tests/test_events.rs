@@ -0,0 +1,123 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use actix_rt::time::delay_for;
+use actix_web::{test, web, App as ActixApp, HttpResponse};
+use yeax::{ActixReactorExt, App, Event, Events, Reactor, Registry};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+struct LoggedIn {
+    username: String,
+}
+
+impl Event for LoggedIn {}
+
+struct LoggedOut;
+
+impl Event for LoggedOut {}
+
+struct Auth;
+
+impl App for Auth {
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route(
+            "/login/{username}",
+            web::get().to(|username: web::Path<String>, events: Events| async move {
+                let username = username.into_inner();
+                events.publish(LoggedIn { username }).await;
+                HttpResponse::Ok().finish().await
+            }),
+        );
+    }
+}
+
+struct Audit {
+    log: Log,
+}
+
+impl App for Audit {
+    fn init(&mut self, r: &mut Registry) {
+        let log = self.log.clone();
+        r.subscribe(0, move |event: &LoggedIn| {
+            log.lock()
+                .unwrap()
+                .push(format!("audit:{}", event.username))
+        });
+
+        let log = self.log.clone();
+        r.subscribe(10, move |event: &LoggedIn| {
+            log.lock().unwrap().push(format!("ban:{}", event.username))
+        });
+
+        let log = self.log.clone();
+        r.subscribe_async(0, move |event: Arc<LoggedIn>| {
+            let log = log.clone();
+            async move {
+                delay_for(Duration::from_millis(1)).await;
+                log.lock().unwrap().push(format!("mail:{}", event.username))
+            }
+        });
+
+        let log = self.log.clone();
+        r.subscribe(-10, move |_: &LoggedOut| {
+            log.lock().unwrap().push("logged_out".to_string())
+        });
+    }
+}
+
+#[actix_rt::test]
+async fn test_publish_by_priority() {
+    let log = Log::default();
+    let reactor = Reactor::shared().add(Audit { log: log.clone() }).build();
+
+    reactor
+        .events()
+        .publish(LoggedIn {
+            username: "admin".to_string(),
+        })
+        .await;
+
+    // same priorities keep the subscription order
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["ban:admin", "audit:admin", "mail:admin"]
+    );
+}
+
+#[actix_rt::test]
+async fn test_publish_to_event_type() {
+    let log = Log::default();
+    let reactor = Reactor::shared().add(Audit { log: log.clone() }).build();
+
+    reactor.events().publish(LoggedOut).await;
+
+    assert_eq!(*log.lock().unwrap(), vec!["logged_out"]);
+}
+
+#[actix_rt::test]
+async fn test_publish_without_subscribers() {
+    let reactor = Reactor::shared().add(Auth).build();
+
+    reactor.events().publish(LoggedOut).await;
+}
+
+#[actix_rt::test]
+async fn test_publish_from_handlers() {
+    let log = Log::default();
+    let reactor = Reactor::shared()
+        .add(Auth)
+        .add(Audit { log: log.clone() })
+        .build()
+        .freeze();
+
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor)).await;
+    let req = test::TestRequest::get().uri("/login/user").to_request();
+    let res = test::call_service(&mut app, req).await;
+
+    assert!(res.status().is_success());
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["ban:user", "audit:user", "mail:user"]
+    );
+}
@@ -0,0 +1,144 @@
+use yeax::{App, AppId, Reactor, Registry};
+
+struct Auth;
+
+impl App for Auth {
+    fn init(&mut self, r: &mut Registry) {
+        // hooks on the app itself are not dependencies
+        r.register_di(|_: &mut Auth| {});
+    }
+}
+
+struct Mailer;
+
+impl App for Mailer {}
+
+struct Banning;
+
+impl App for Banning {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Auth>()]
+    }
+
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|_: &mut Auth, _: Option<&Mailer>| {});
+    }
+}
+
+fn reactor() -> Reactor {
+    Reactor::default()
+        .add(Auth)
+        .add_named("alerts", Mailer)
+        .add(Mailer)
+        .add(Banning)
+}
+
+#[test]
+fn test_graph() {
+    let graph = reactor().build().graph();
+
+    let names: Vec<_> = graph.apps.iter().map(|app| app.name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "test_graph::Auth",
+            "test_graph::Mailer",
+            "test_graph::Mailer",
+            "test_graph::Banning"
+        ]
+    );
+    assert_eq!(graph.apps[1].instance, Some("alerts"));
+
+    let edges: Vec<_> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            (
+                edge.from,
+                edge.to,
+                edge.hook.is_some(),
+                edge.mutable,
+                edge.optional,
+            )
+        })
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            (3, 0, false, false, false),
+            (3, 0, true, true, false),
+            (3, 2, true, false, true)
+        ]
+    );
+    // hooks are named by the apps they ask for
+    assert_eq!(
+        graph.edges[1].hook.as_deref(),
+        Some("(&mut test_graph::Auth, Option<&test_graph::Mailer>)")
+    );
+}
+
+#[test]
+fn test_graph_before_build() {
+    // only the declared dependencies are known before the apps are initialized
+    let graph = reactor().graph();
+    assert_eq!(graph.edges.len(), 1);
+}
+
+#[test]
+fn test_dot_export() {
+    let dot = reactor().build().graph().to_dot();
+
+    assert!(dot.starts_with("digraph apps {\n"));
+    assert!(dot.contains("    1 [label=\"test_graph::Mailer (alerts)\"];\n"));
+    assert!(dot.contains("    3 -> 0;\n"));
+    assert!(dot.contains("style=bold"));
+    assert!(dot.contains("style=dashed"));
+    assert!(dot.ends_with('}'));
+}
+
+#[test]
+fn test_json_export() {
+    let json = reactor().build().graph().to_json();
+    let graph: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(graph["apps"][1]["instance"], "alerts");
+    assert_eq!(graph["apps"][2]["instance"], serde_json::Value::Null);
+    assert_eq!(graph["edges"][2]["optional"], true);
+    assert_eq!(graph["edges"].as_array().unwrap().len(), 3);
+}
+
+struct Notifier;
+
+impl App for Notifier {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|_: &Notifier, _: Option<&mut Mailer>| {});
+    }
+}
+
+#[test]
+fn test_dot_optional_mutable_edge() {
+    let dot = Reactor::default()
+        .add(Mailer)
+        .add(Notifier)
+        .build()
+        .graph()
+        .to_dot();
+
+    let label = "(&test_graph::Notifier, Option<&mut test_graph::Mailer>)";
+    let edge = format!("    1 -> 0 [label=\"{}\", style=\"bold,dashed\"];\n", label);
+    assert!(dot.contains(&edge));
+}
+
+#[test]
+fn test_frozen_graph() {
+    let reactor = Reactor::shared()
+        .add(Auth)
+        .add_named("alerts", Mailer)
+        .add(Mailer)
+        .add(Banning)
+        .build()
+        .freeze();
+
+    // the hooks are kept in the graph once frozen
+    assert_eq!(reactor.graph().edges.len(), 3);
+}
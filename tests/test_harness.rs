@@ -0,0 +1,111 @@
+use actix_web::http::StatusCode;
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use yeax::test::{read_error, read_json, read_response, TestApp};
+use yeax::{App, AppRef, Reactor, SharedReactor};
+use yeax_api::{JsonError, JsonResponse};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct User {
+    name: String,
+}
+
+struct Users {
+    names: Vec<&'static str>,
+}
+
+impl App for Users {
+    fn mount_point(&self) -> &str {
+        "/users"
+    }
+
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("", web::get().to(list))
+            .route("", web::post().to(create))
+            .route("/{idx}", web::get().to(retrieve));
+    }
+}
+
+async fn list(users: AppRef<Users>) -> JsonResponse<Vec<User>> {
+    let users: Vec<_> = users
+        .names
+        .iter()
+        .map(|name| User {
+            name: name.to_string(),
+        })
+        .collect();
+    let total = users.len();
+    JsonResponse::with_content(users).total(total)
+}
+
+async fn create(user: web::Json<User>) -> JsonResponse<User> {
+    JsonResponse::with_content(user.into_inner())
+}
+
+async fn retrieve(
+    idx: web::Path<usize>,
+    users: AppRef<Users>,
+) -> Result<JsonResponse<User>, JsonError> {
+    match users.names.get(idx.into_inner()) {
+        Some(name) => Ok(JsonResponse::with_content(User {
+            name: name.to_string(),
+        })),
+        None => Err(JsonError::new(404, "user_not_found").hint("No such user".to_string())),
+    }
+}
+
+fn reactor() -> SharedReactor {
+    Reactor::shared().add(Users {
+        names: vec!["admin", "user"],
+    })
+}
+
+#[actix_rt::test]
+async fn test_read_response() {
+    let mut app = TestApp::new(reactor()).await;
+
+    let res = app.get("/users").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body = read_response::<Vec<User>>(res).await;
+    assert_eq!(body.status, 200);
+    assert_eq!(body.total, Some(2));
+    assert_eq!(body.content.unwrap()[1].name, "user");
+}
+
+#[actix_rt::test]
+async fn test_post_json() {
+    let mut app = TestApp::new(reactor()).await;
+
+    let user = User {
+        name: "new".to_string(),
+    };
+    let res = app.post("/users", &user).await;
+    let body = read_response::<User>(res).await;
+    assert_eq!(body.content, Some(user));
+}
+
+#[actix_rt::test]
+async fn test_read_error() {
+    let mut app = TestApp::new(reactor()).await;
+
+    let res = app.get("/users/5").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let err = read_error::<()>(res).await;
+    assert_eq!(err.status, 404);
+    assert_eq!(err.code, "user_not_found");
+    assert_eq!(err.hint.as_deref(), Some("No such user"));
+    assert!(err.content.is_none());
+}
+
+#[actix_rt::test]
+async fn test_shared_reactor() {
+    let reactor = reactor().build().freeze();
+    let mut app = TestApp::with_frozen(reactor.clone()).await;
+
+    let res = app.get("/users/0").await;
+    let user: serde_json::Value = read_json(res).await;
+    assert_eq!(user["content"]["name"], "admin");
+    assert_eq!(app.reactor().get::<Users>().unwrap().names.len(), 2);
+}
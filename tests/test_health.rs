@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use actix_rt::time::delay_for;
+use actix_web::test;
+use actix_web::App as ActixApp;
+use futures_util::future::{ready, LocalBoxFuture};
+use serde_json::{json, Value};
+use yeax::test::{read_response, TestApp};
+use yeax::{ActixReactorExt, App, Reactor, SharedReactor};
+
+struct Database {
+    healthy: bool,
+}
+
+impl App for Database {
+    fn health(&self) -> Option<LocalBoxFuture<'_, Result<(), String>>> {
+        let result = match self.healthy {
+            true => Ok(()),
+            false => Err("connection refused".to_string()),
+        };
+        Some(Box::pin(ready(result)))
+    }
+}
+
+struct Cache;
+
+impl App for Cache {
+    fn health(&self) -> Option<LocalBoxFuture<'_, Result<(), String>>> {
+        Some(Box::pin(async {
+            delay_for(Duration::from_secs(3600)).await;
+            Ok(())
+        }))
+    }
+}
+
+// has no health check
+struct Pages;
+
+impl App for Pages {}
+
+fn reactor() -> SharedReactor {
+    Reactor::shared().with_health_endpoint("/_health", Duration::from_millis(50))
+}
+
+#[actix_rt::test]
+async fn test_healthy() {
+    let reactor = reactor()
+        .add(Database { healthy: true })
+        .add_named("replica", Database { healthy: true })
+        .add(Pages);
+    let mut app = TestApp::new(reactor).await;
+
+    let res = app.get("/_health").await;
+    assert_eq!(res.status(), 200);
+    let body = read_response::<Value>(res).await;
+    assert_eq!(
+        body.content.unwrap(),
+        json!({
+            "healthy": true,
+            "apps": [
+                {"app": "test_health::Database", "instance": null, "healthy": true},
+                {"app": "test_health::Database", "instance": "replica", "healthy": true},
+            ]
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn test_unhealthy() {
+    let reactor = reactor().add(Database { healthy: false }).add(Cache);
+    let mut app = TestApp::new(reactor).await;
+
+    // the checks run concurrently, so the slow one doesn't delay the others
+    let res = app.get("/_health").await;
+    assert_eq!(res.status(), 503);
+    let body = read_response::<Value>(res).await;
+    assert_eq!(body.status, 503);
+    assert_eq!(
+        body.content.unwrap(),
+        json!({
+            "healthy": false,
+            "apps": [
+                {
+                    "app": "test_health::Database",
+                    "instance": null,
+                    "healthy": false,
+                    "error": "connection refused"
+                },
+                {
+                    "app": "test_health::Cache",
+                    "instance": null,
+                    "healthy": false,
+                    "error": "timed out after 50ms"
+                },
+            ]
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn test_health_without_frozen_reactor() {
+    let reactor = reactor().add(Database { healthy: true }).build();
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor)).await;
+
+    let req = test::TestRequest::get().uri("/_health").to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), 500);
+}
@@ -0,0 +1,204 @@
+use futures_util::future::LocalBoxFuture;
+use yeax::{App, BuildError, Inject, InstanceName, Named, Reactor, Registry, SharedReactor};
+
+macro_rules! counters {
+    ($($name:ident),*) => {
+        $(
+            #[derive(Default)]
+            struct $name {
+                count: u32,
+            }
+
+            impl App for $name {}
+        )*
+    };
+}
+
+counters!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13);
+
+/// More apps than a hook can take as parameters
+#[derive(Inject)]
+struct Counters<'a> {
+    c1: &'a mut C1,
+    c2: &'a mut C2,
+    c3: &'a mut C3,
+    c4: &'a mut C4,
+    c5: &'a mut C5,
+    c6: &'a mut C6,
+    c7: &'a mut C7,
+    c8: &'a mut C8,
+    c9: &'a mut C9,
+    c10: &'a mut C10,
+    c11: &'a mut C11,
+    c12: &'a mut C12,
+    c13: &'a mut C13,
+}
+
+struct Staff;
+
+impl InstanceName for Staff {
+    const NAME: &'static str = "staff";
+}
+
+#[derive(Default)]
+struct Auth {
+    logins: u32,
+}
+
+impl App for Auth {}
+
+struct Mail;
+
+impl App for Mail {}
+
+#[derive(Inject)]
+struct Accounts<'a>(&'a mut Auth, Named<Staff, &'a mut Auth>, Option<&'a Mail>);
+
+#[derive(Inject)]
+struct Deps<'a> {
+    counters: Counters<'a>,
+    accounts: Accounts<'a>,
+}
+
+struct Hub;
+
+impl App for Hub {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|deps: Deps, _: &Hub| {
+            let Deps { counters, accounts } = deps;
+            let Counters {
+                c1,
+                c2,
+                c3,
+                c4,
+                c5,
+                c6,
+                c7,
+                c8,
+                c9,
+                c10,
+                c11,
+                c12,
+                c13,
+            } = counters;
+            let counts = vec![
+                &mut c1.count,
+                &mut c2.count,
+                &mut c3.count,
+                &mut c4.count,
+                &mut c5.count,
+                &mut c6.count,
+                &mut c7.count,
+                &mut c8.count,
+                &mut c9.count,
+                &mut c10.count,
+                &mut c11.count,
+                &mut c12.count,
+                &mut c13.count,
+            ];
+            for (count, n) in counts.into_iter().zip(1..) {
+                *count += n;
+            }
+
+            let Accounts(auth, mut staff, mail) = accounts;
+            auth.logins += 1;
+            staff.logins += 2;
+            assert!(mail.is_none());
+        });
+        r.register_di_async(add_logins);
+    }
+}
+
+fn add_logins(accounts: Accounts<'_>) -> LocalBoxFuture<'_, ()> {
+    Box::pin(async move {
+        let Accounts(auth, mut staff, _) = accounts;
+        auth.logins += 10;
+        staff.logins += 20;
+    })
+}
+
+fn reactor() -> SharedReactor {
+    Reactor::shared()
+        .add(Hub)
+        .add(C1::default())
+        .add(C2::default())
+        .add(C3::default())
+        .add(C4::default())
+        .add(C5::default())
+        .add(C6::default())
+        .add(C7::default())
+        .add(C8::default())
+        .add(C9::default())
+        .add(C10::default())
+        .add(C11::default())
+        .add(C12::default())
+        .add(C13::default())
+        .add(Auth::default())
+}
+
+#[actix_rt::test]
+async fn test_inject_struct() {
+    let reactor = reactor()
+        .add_named("staff", Auth::default())
+        .build_async()
+        .await
+        .freeze();
+
+    assert_eq!(reactor.get::<C1>().unwrap().count, 1);
+    assert_eq!(reactor.get::<C7>().unwrap().count, 7);
+    assert_eq!(reactor.get::<C13>().unwrap().count, 13);
+    assert_eq!(reactor.get::<Auth>().unwrap().logins, 11);
+    assert_eq!(reactor.get_named::<Auth>("staff").unwrap().logins, 22);
+}
+
+#[test]
+fn test_inject_struct_errors() {
+    let err = reactor().try_build().err().unwrap();
+    assert!(matches!(
+        err.errors(),
+        [
+            BuildError::MissingParam {
+                param: "test_inject::Auth",
+                ..
+            },
+            BuildError::MissingParam {
+                param: "test_inject::Auth",
+                ..
+            },
+        ]
+    ));
+
+    #[derive(Inject)]
+    struct Conflicting<'a> {
+        _auth: &'a mut Auth,
+        _accounts: Accounts<'a>,
+    }
+
+    struct Broken;
+
+    impl App for Broken {
+        fn init(&mut self, r: &mut Registry) {
+            r.register_di(|_: Conflicting| {});
+        }
+    }
+
+    let err = Reactor::shared()
+        .add(Broken)
+        .add(Auth::default())
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.errors(),
+        [BuildError::DuplicateParam {
+            param: "test_inject::Auth",
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_inject_derive_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/inject_*.rs");
+}
@@ -0,0 +1,118 @@
+use actix_web::{web, HttpResponse};
+use yeax::test::TestApp;
+use yeax::{App, Counter, Reactor, Registry};
+
+#[derive(Default)]
+struct Mailer {
+    sent: Option<Counter>,
+}
+
+impl App for Mailer {
+    fn init(&mut self, r: &mut Registry) {
+        self.sent = Some(r.counter("emails_sent_total", "The emails sent"));
+        // the same counter is returned
+        r.counter("emails_sent_total", "The emails sent").add(2);
+    }
+
+    fn mount_point(&self) -> &'static str {
+        "/mail"
+    }
+
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route(
+            "/{id}",
+            web::get().to(|id: web::Path<u32>| match id.into_inner() {
+                0 => HttpResponse::NotFound(),
+                _ => HttpResponse::Ok(),
+            }),
+        );
+    }
+}
+
+struct Pages;
+
+impl App for Pages {
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("/about", web::get().to(HttpResponse::Ok));
+    }
+}
+
+struct Docs;
+
+impl App for Docs {
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("/docs", web::get().to(HttpResponse::Ok));
+    }
+}
+
+fn lines(metrics: &str, prefix: &str) -> Vec<String> {
+    metrics
+        .lines()
+        .filter(|line| line.starts_with(prefix))
+        .map(String::from)
+        .collect()
+}
+
+#[actix_rt::test]
+async fn test_request_metrics() {
+    let reactor = Reactor::shared()
+        .with_metrics_endpoint("/metrics")
+        .add(Mailer::default())
+        .add_named("staff", Mailer::default())
+        .add(Pages)
+        .add(Docs);
+    let mut app = TestApp::new(reactor).await;
+
+    app.get("/mail/1").await;
+    app.get("/mail/2").await;
+    app.get("/mail/0").await;
+    app.get("/about").await;
+    app.get("/docs").await;
+    assert_eq!(app.get("/missing").await.status(), 404);
+
+    let res = app.get("/metrics").await;
+    assert_eq!(res.status(), 200);
+    let body = actix_web::test::read_body(res).await;
+    let metrics = String::from_utf8(body.to_vec()).unwrap();
+
+    // the requests are attributed by the scope of the app, the unserved ones to no app
+    assert_eq!(
+        lines(&metrics, "yeax_requests_total{"),
+        vec![
+            r#"yeax_requests_total{app="",route="",status="4xx"} 1"#,
+            r#"yeax_requests_total{app="test_metrics::Docs",route="/docs",status="2xx"} 1"#,
+            r#"yeax_requests_total{app="test_metrics::Mailer",route="/mail/{id}",status="2xx"} 2"#,
+            r#"yeax_requests_total{app="test_metrics::Mailer",route="/mail/{id}",status="4xx"} 1"#,
+            r#"yeax_requests_total{app="test_metrics::Pages",route="/about",status="2xx"} 1"#,
+        ]
+    );
+    assert!(metrics.contains(
+        r#"yeax_request_duration_seconds_bucket{app="test_metrics::Mailer",route="/mail/{id}",le="+Inf"} 3"#
+    ));
+    assert!(metrics.contains(
+        r#"yeax_request_duration_seconds_count{app="test_metrics::Pages",route="/about"} 1"#
+    ));
+
+    assert_eq!(
+        lines(&metrics, "emails_sent_total"),
+        vec![
+            r#"emails_sent_total{app="test_metrics::Mailer"} 2"#,
+            r#"emails_sent_total{app="test_metrics::Mailer",instance="staff"} 2"#,
+        ]
+    );
+    assert_eq!(lines(&metrics, "# TYPE emails_sent_total").len(), 1);
+}
+
+#[actix_rt::test]
+async fn test_counters() {
+    let reactor = Reactor::shared().add(Mailer::default()).build().freeze();
+
+    let mailer = reactor.get::<Mailer>().unwrap();
+    mailer.sent.as_ref().unwrap().inc();
+    assert_eq!(mailer.sent.as_ref().unwrap().get(), 3);
+
+    // without the endpoint the requests are not recorded, but the counters are still reported
+    let metrics = reactor.metrics();
+    assert!(!metrics.contains("yeax_requests_total{"));
+    assert!(metrics.contains(r#"emails_sent_total{app="test_metrics::Mailer"} 3"#));
+}
@@ -0,0 +1,174 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App as ActixApp, HttpResponse};
+use yeax::{
+    ActixMiddlewareExt, ActixReactorExt, App, AppId, BuildError, MiddlewareConfig, Next, Reactor,
+};
+
+/// Appends `name` to the `x-trace` header of the response
+fn trace(cfg: &mut MiddlewareConfig, name: &'static str, scoped: bool) {
+    let middleware = move |req: ServiceRequest, next: Next| async move {
+        let mut res = next.call(req).await?;
+        let headers = res.headers_mut();
+        let trace = match headers.get("x-trace") {
+            Some(value) => format!("{},{}", value.to_str().unwrap(), name),
+            None => name.to_string(),
+        };
+        headers.insert(
+            HeaderName::from_static("x-trace"),
+            HeaderValue::from_str(&trace).unwrap(),
+        );
+        Ok(res)
+    };
+    if scoped {
+        cfg.wrap_scoped(middleware);
+    } else {
+        cfg.wrap(middleware);
+    }
+}
+
+struct Sessions;
+
+impl App for Sessions {
+    fn configure_middleware(&self, cfg: &mut MiddlewareConfig) {
+        trace(cfg, "inner", false);
+        trace(cfg, "outer", false);
+    }
+}
+
+struct Admin;
+
+impl App for Admin {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Sessions>()]
+    }
+
+    fn mount_point(&self) -> &str {
+        "/admin"
+    }
+
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("/users", web::get().to(|| HttpResponse::Ok().finish()));
+    }
+
+    fn configure_middleware(&self, cfg: &mut MiddlewareConfig) {
+        trace(cfg, "admin", false);
+        trace(cfg, "guard", true);
+    }
+}
+
+struct Blog;
+
+impl App for Blog {
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("/posts", web::get().to(|| HttpResponse::Ok().finish()));
+    }
+}
+
+async fn trace_of(reactor: &Reactor, path: &str) -> (StatusCode, String) {
+    let app = ActixApp::new().configure_app(reactor).wrap_reactor(reactor);
+    let mut app = test::init_service(app).await;
+    let res = test::call_service(&mut app, test::TestRequest::get().uri(path).to_request()).await;
+    let trace = res
+        .headers()
+        .get("x-trace")
+        .map_or("", |value| value.to_str().unwrap());
+    (res.status(), trace.to_string())
+}
+
+#[actix_rt::test]
+async fn test_middleware_order() {
+    // Admin is added first but initialized after Sessions
+    let reactor = Reactor::default()
+        .add(Admin)
+        .add(Sessions)
+        .add(Blog)
+        .build();
+
+    // the responses pass through the innermost middleware first
+    assert_eq!(
+        trace_of(&reactor, "/admin/users").await,
+        (StatusCode::OK, "guard,admin,outer,inner".to_string())
+    );
+}
+
+#[actix_rt::test]
+async fn test_scoped_middleware() {
+    let reactor = Reactor::default()
+        .add(Sessions)
+        .add(Admin)
+        .add(Blog)
+        .build();
+
+    assert_eq!(
+        trace_of(&reactor, "/posts").await,
+        (StatusCode::OK, "admin,outer,inner".to_string())
+    );
+    // the global middleware wrap the requests matching no service too
+    assert_eq!(
+        trace_of(&reactor, "/missing").await,
+        (StatusCode::NOT_FOUND, "admin,outer,inner".to_string())
+    );
+}
+
+#[actix_rt::test]
+async fn test_scoped_middleware_at_overridden_mount_point() {
+    let reactor = Reactor::shared()
+        .add(Sessions)
+        .add_at("/staff", Admin)
+        .build()
+        .freeze();
+
+    let app = ActixApp::new()
+        .configure_app(&reactor)
+        .wrap_reactor(&reactor);
+    let mut app = test::init_service(app).await;
+    let req = test::TestRequest::get().uri("/staff/users").to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(
+        res.headers().get("x-trace").unwrap(),
+        "guard,admin,outer,inner"
+    );
+}
+
+#[test]
+fn test_scoped_middleware_without_mount_point() {
+    let err = Reactor::default()
+        .add(Sessions)
+        .add_at("", Admin)
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [BuildError::UnmountedMiddleware {
+            app: "test_middleware::Admin"
+        }]
+    ));
+}
+
+#[test]
+fn test_unmounted_middleware_reported_before_init() {
+    // reported along with the missing dependencies, before any app is initialized
+    let err = Reactor::default()
+        .add_at("", Admin)
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [
+            BuildError::MissingApp {
+                app: "test_middleware::Admin",
+                dependency: "test_middleware::Sessions",
+                instance: None,
+            },
+            BuildError::UnmountedMiddleware {
+                app: "test_middleware::Admin"
+            }
+        ]
+    ));
+}
@@ -0,0 +1,315 @@
+use futures_util::future::{ready, LocalBoxFuture};
+use yeax::{
+    App, AppId, BuildError, MigrateError, Migration, MigrationExecutor, MigrationStatus, Reactor,
+};
+
+/// Keeps the applied migrations in memory, recording the SQL it runs
+#[derive(Default)]
+struct FakeExecutor {
+    applied: Vec<(String, String)>,
+    executed: Vec<String>,
+}
+
+impl FakeExecutor {
+    fn run(&mut self, sql: &str) -> Result<(), String> {
+        if sql.contains("INVALID") {
+            return Err("syntax error".to_string());
+        }
+        self.executed.push(sql.to_string());
+        Ok(())
+    }
+}
+
+impl MigrationExecutor for FakeExecutor {
+    fn applied(&mut self) -> LocalBoxFuture<'_, Result<Vec<(String, String)>, String>> {
+        Box::pin(ready(Ok(self.applied.clone())))
+    }
+
+    fn apply<'a>(
+        &'a mut self,
+        app: &'a str,
+        name: &'a str,
+        sql: &'a str,
+    ) -> LocalBoxFuture<'a, Result<(), String>> {
+        let result = self.run(sql).map(|()| {
+            self.applied.push((app.to_string(), name.to_string()));
+        });
+        Box::pin(ready(result))
+    }
+
+    fn revert<'a>(
+        &'a mut self,
+        app: &'a str,
+        name: &'a str,
+        sql: &'a str,
+    ) -> LocalBoxFuture<'a, Result<(), String>> {
+        let result = self.run(sql).map(|()| {
+            self.applied
+                .retain(|applied| *applied != (app.into(), name.into()));
+        });
+        Box::pin(ready(result))
+    }
+}
+
+struct Auth;
+
+impl App for Auth {
+    fn migrations_namespace(&self) -> Option<&'static str> {
+        Some("auth")
+    }
+
+    fn migrations(&self) -> Vec<Migration> {
+        vec![
+            Migration::new("0001_users", "CREATE TABLE users").down("DROP TABLE users"),
+            Migration::new("0002_email", "ALTER TABLE users ADD email")
+                .down("ALTER TABLE users DROP email"),
+        ]
+    }
+}
+
+struct Banning;
+
+impl App for Banning {
+    fn migrations_namespace(&self) -> Option<&'static str> {
+        Some("banning")
+    }
+
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Auth>()]
+    }
+
+    fn migrations(&self) -> Vec<Migration> {
+        vec![Migration::new("0001_bans", "CREATE TABLE bans")]
+    }
+}
+
+struct Broken;
+
+impl App for Broken {
+    fn migrations_namespace(&self) -> Option<&'static str> {
+        Some("broken")
+    }
+
+    fn migrations(&self) -> Vec<Migration> {
+        vec![Migration::new("0001_invalid", "INVALID")]
+    }
+}
+
+fn status(app: &'static str, name: &'static str, applied: bool) -> MigrationStatus {
+    MigrationStatus { app, name, applied }
+}
+
+#[actix_rt::test]
+async fn test_migrate_in_dependency_order() {
+    let migrator = Reactor::default()
+        .add(Banning)
+        .add(Auth)
+        .migrator()
+        .unwrap();
+    let mut executor = FakeExecutor::default();
+
+    let applied = migrator.migrate(&mut executor).await.unwrap();
+    assert_eq!(applied.len(), 3);
+    assert_eq!(
+        executor.executed,
+        vec![
+            "CREATE TABLE users",
+            "ALTER TABLE users ADD email",
+            "CREATE TABLE bans"
+        ]
+    );
+
+    // applied migrations are skipped
+    assert!(migrator.migrate(&mut executor).await.unwrap().is_empty());
+    assert_eq!(
+        migrator.status(&mut executor).await.unwrap(),
+        vec![
+            status("auth", "0001_users", true),
+            status("auth", "0002_email", true),
+            status("banning", "0001_bans", true),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_rollback() {
+    let migrator = Reactor::default()
+        .add(Auth)
+        .add(Banning)
+        .migrator()
+        .unwrap();
+    let mut executor = FakeExecutor::default();
+    migrator.migrate(&mut executor).await.unwrap();
+
+    // the migration of Banning can't be rolled back
+    let err = migrator.rollback(&mut executor, 2).await.unwrap_err();
+    assert!(matches!(
+        err,
+        MigrateError::Irreversible {
+            app: "banning",
+            migration: "0001_bans"
+        }
+    ));
+    assert_eq!(executor.applied.len(), 3);
+
+    let migrator = Reactor::default().add(Auth).migrator().unwrap();
+    let reverted = migrator.rollback(&mut executor, 1).await.unwrap();
+    assert_eq!(reverted, vec![status("auth", "0002_email", false)]);
+    assert_eq!(
+        migrator.status(&mut executor).await.unwrap(),
+        vec![
+            status("auth", "0001_users", true),
+            status("auth", "0002_email", false),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_rollback_in_applied_order() {
+    struct Notes;
+
+    impl App for Notes {
+        fn migrations_namespace(&self) -> Option<&'static str> {
+            Some("notes")
+        }
+
+        fn dependencies(&self) -> Vec<AppId> {
+            vec![AppId::of::<Auth>()]
+        }
+
+        fn migrations(&self) -> Vec<Migration> {
+            vec![Migration::new("0001_notes", "CREATE TABLE notes").down("DROP TABLE notes")]
+        }
+    }
+
+    // `0002_email` was added to Auth after the migration of Notes was applied
+    let mut executor = FakeExecutor {
+        applied: vec![
+            ("auth".to_string(), "0001_users".to_string()),
+            ("notes".to_string(), "0001_notes".to_string()),
+        ],
+        ..FakeExecutor::default()
+    };
+    let migrator = Reactor::default().add(Auth).add(Notes).migrator().unwrap();
+    migrator.migrate(&mut executor).await.unwrap();
+
+    let reverted = migrator.rollback(&mut executor, 1).await.unwrap();
+    assert_eq!(reverted, vec![status("auth", "0002_email", false)]);
+    let reverted = migrator.rollback(&mut executor, 1).await.unwrap();
+    assert_eq!(reverted, vec![status("notes", "0001_notes", false)]);
+    assert_eq!(
+        executor.executed,
+        vec![
+            "ALTER TABLE users ADD email",
+            "ALTER TABLE users DROP email",
+            "DROP TABLE notes"
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_failed_migration() {
+    let migrator = Reactor::default()
+        .add(Auth)
+        .add(Broken)
+        .add(Banning)
+        .migrator()
+        .unwrap();
+    let mut executor = FakeExecutor::default();
+
+    let err = migrator.migrate(&mut executor).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "migration `0001_invalid` of `broken` failed: syntax error"
+    );
+    // the migrations before the failed one stay applied
+    assert_eq!(executor.applied.len(), 2);
+}
+
+#[test]
+fn test_migrator_missing_dependency() {
+    let err = Reactor::default().add(Banning).migrator().err().unwrap();
+
+    assert!(matches!(err.errors(), [BuildError::MissingApp { .. }]));
+}
+
+#[test]
+fn test_migrations_namespaces() {
+    struct Unlabeled;
+
+    impl App for Unlabeled {
+        fn migrations(&self) -> Vec<Migration> {
+            vec![Migration::new("0001_logs", "CREATE TABLE logs")]
+        }
+    }
+
+    // apps without migrations don't need a namespace
+    struct Plain;
+
+    impl App for Plain {}
+
+    let err = Reactor::default()
+        .add(Auth)
+        .add_named("staff", Auth)
+        .add(Unlabeled)
+        .add(Plain)
+        .migrator()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [
+            BuildError::DuplicateNamespace {
+                namespace: "auth",
+                apps: ("test_migrate::Auth", "test_migrate::Auth")
+            },
+            BuildError::UnlabeledMigrations {
+                app: "test_migrate::test_migrations_namespaces::Unlabeled"
+            }
+        ]
+    ));
+}
+
+#[cfg(feature = "sqlx")]
+#[actix_rt::test]
+async fn test_sqlite() {
+    use sqlx::any::AnyPoolOptions;
+
+    struct Notes;
+
+    impl App for Notes {
+        fn migrations_namespace(&self) -> Option<&'static str> {
+            Some("notes")
+        }
+
+        fn migrations(&self) -> Vec<Migration> {
+            vec![
+                Migration::new("0001_notes", "CREATE TABLE notes (body TEXT NOT NULL)")
+                    .down("DROP TABLE notes"),
+            ]
+        }
+    }
+
+    // every connection to an in-memory database opens a new one
+    let mut pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let migrator = Reactor::default().add(Notes).migrator().unwrap();
+
+    migrator.migrate(&mut pool).await.unwrap();
+    sqlx::query("INSERT INTO notes (body) VALUES ('hello')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(migrator.status(&mut pool).await.unwrap()[0].applied);
+
+    migrator.rollback(&mut pool, 1).await.unwrap();
+    assert!(!migrator.status(&mut pool).await.unwrap()[0].applied);
+    assert!(sqlx::query("SELECT * FROM notes")
+        .fetch_all(&pool)
+        .await
+        .is_err());
+}
@@ -0,0 +1,191 @@
+use std::sync::{Arc, Mutex};
+
+use yeax::{App, AppId, BuildError, Reactor, Registry};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+struct Mailer {
+    log: Log,
+    sender: &'static str,
+}
+
+impl App for Mailer {
+    fn post_init(&mut self) {
+        let entry = format!("mailer:{}", self.sender);
+        self.log.lock().unwrap().push(entry);
+    }
+}
+
+struct FakeMailer {
+    log: Log,
+}
+
+impl App for FakeMailer {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|fake: &FakeMailer| fake.log.lock().unwrap().push("fake".to_string()));
+    }
+}
+
+struct Signup {
+    log: Log,
+}
+
+impl App for Signup {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Mailer>()]
+    }
+
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|_: &Signup, mailer: &mut Mailer| mailer.sender = "signup");
+        r.register_di(|me: &Signup, mailer: Option<&Mailer>| {
+            let entry = format!("optional:{}", mailer.is_some());
+            me.log.lock().unwrap().push(entry);
+        });
+    }
+}
+
+struct Newsletter {
+    log: Log,
+}
+
+impl App for Newsletter {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Mailer>()]
+    }
+
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|me: &Newsletter, mailer: Option<&Mailer>| {
+            let entry = format!("optional:{}", mailer.is_some());
+            me.log.lock().unwrap().push(entry);
+        });
+    }
+}
+
+fn mailer(log: &Log) -> Mailer {
+    Mailer {
+        log: log.clone(),
+        sender: "default",
+    }
+}
+
+#[test]
+fn test_replace() {
+    let log = Log::default();
+    let _ = Reactor::shared()
+        .add(mailer(&log))
+        .add(Signup { log: log.clone() })
+        .replace(Mailer {
+            log: log.clone(),
+            sender: "test",
+        })
+        .build();
+
+    // the hooks of the other apps are run on the replacement
+    assert_eq!(*log.lock().unwrap(), vec!["optional:true", "mailer:signup"]);
+}
+
+#[test]
+fn test_replace_named() {
+    let log = Log::default();
+    let _ = Reactor::shared()
+        .add(mailer(&log))
+        .add_named("billing", mailer(&log))
+        .replace_named(
+            "billing",
+            Mailer {
+                log: log.clone(),
+                sender: "test",
+            },
+        )
+        .build();
+
+    assert_eq!(*log.lock().unwrap(), vec!["mailer:default", "mailer:test"]);
+}
+
+#[test]
+fn test_replace_missing_app() {
+    let log = Log::default();
+    let err = Reactor::shared()
+        .add(Signup { log: log.clone() })
+        .replace(mailer(&log))
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [
+            BuildError::NotRegistered {
+                app: "test_replace::Mailer"
+            },
+            BuildError::MissingApp { .. }
+        ]
+    ));
+}
+
+#[test]
+fn test_stand_in() {
+    let log = Log::default();
+    let _ = Reactor::shared()
+        .add(mailer(&log))
+        .add(Newsletter { log: log.clone() })
+        .stand_in::<Mailer, _>(FakeMailer { log: log.clone() })
+        .build();
+
+    // the optional hooks asking for the mailer don't receive it
+    assert_eq!(*log.lock().unwrap(), vec!["fake", "optional:false"]);
+}
+
+#[test]
+fn test_stand_in_reports_required_hooks() {
+    let log = Log::default();
+    let err = Reactor::shared()
+        .add(mailer(&log))
+        .add(Signup { log: log.clone() })
+        .stand_in::<Mailer, _>(FakeMailer { log: log.clone() })
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [BuildError::ReplacedParam {
+            app: "test_replace::Signup",
+            param: "test_replace::Mailer",
+            stand_in: "test_replace::FakeMailer",
+            ..
+        }]
+    ));
+    // no hook is run
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_stand_in_missing_app() {
+    let log = Log::default();
+    let err = Reactor::shared()
+        .stand_in::<Mailer, _>(FakeMailer { log })
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [BuildError::NotRegistered {
+            app: "test_replace::Mailer"
+        }]
+    ));
+}
+
+#[test]
+fn test_frozen_stand_in() {
+    let log = Log::default();
+    let reactor = Reactor::shared()
+        .add(mailer(&log))
+        .stand_in::<Mailer, _>(FakeMailer { log })
+        .build()
+        .freeze();
+
+    assert!(reactor.get::<Mailer>().is_none());
+    assert!(reactor.get::<FakeMailer>().is_some());
+}
@@ -0,0 +1,157 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use actix_web::{App as ActixApp, HttpServer};
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_util::future::{pending, LocalBoxFuture};
+use futures_util::StreamExt;
+use yeax::{ActixReactorExt, App, AppId, FrozenReactor, Reactor, Registry, ShutdownError};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+struct Pool {
+    log: Log,
+    // told when the background task starts and when it's dropped
+    signals: UnboundedSender<&'static str>,
+}
+
+impl App for Pool {
+    fn init(&mut self, r: &mut Registry) {
+        r.spawn(|reactor: FrozenReactor| async move {
+            let pool = reactor.get::<Pool>().unwrap();
+            let _guard = Guard(pool.log.clone(), pool.signals.clone());
+            let _ = pool.signals.unbounded_send("started");
+            pending::<()>().await;
+        });
+    }
+
+    fn finish(&mut self) {
+        self.log.lock().unwrap().push("pool".to_string());
+    }
+}
+
+/// Records when the background task is dropped
+struct Guard(Log, UnboundedSender<&'static str>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().push("task".to_string());
+        let _ = self.1.unbounded_send("stopped");
+    }
+}
+
+struct Mailer {
+    log: Log,
+    // waits forever in `finish`
+    stuck: bool,
+}
+
+impl App for Mailer {
+    fn dependencies(&self) -> Vec<AppId> {
+        vec![AppId::of::<Pool>()]
+    }
+
+    fn finish_async(&mut self) -> LocalBoxFuture<'_, ()> {
+        Box::pin(async move {
+            if self.stuck {
+                pending::<()>().await;
+            }
+            self.log.lock().unwrap().push("mailer".to_string());
+        })
+    }
+}
+
+/// A reactor whose background task is running
+async fn reactor(log: &Log, stuck: bool) -> (FrozenReactor, UnboundedReceiver<&'static str>) {
+    let (signals, mut received) = mpsc::unbounded();
+    let reactor = Reactor::shared()
+        .add(Mailer {
+            log: log.clone(),
+            stuck,
+        })
+        .add(Pool {
+            log: log.clone(),
+            signals,
+        })
+        .build()
+        .freeze();
+    reactor.start_tasks();
+    assert_eq!(received.next().await, Some("started"));
+    (reactor, received)
+}
+
+#[actix_rt::test]
+async fn test_shutdown() {
+    let log = Log::default();
+    let (reactor, _) = reactor(&log, false).await;
+
+    reactor.shutdown(Duration::from_secs(5)).await.unwrap();
+    // the tasks are stopped first, and the apps are finished in the reverse order
+    assert_eq!(*log.lock().unwrap(), vec!["task", "mailer", "pool"]);
+}
+
+#[actix_rt::test]
+async fn test_serve() {
+    let log = Log::default();
+    let (reactor, _) = reactor(&log, false).await;
+
+    let weak = reactor.weak();
+    let server = HttpServer::new(move || ActixApp::new().configure_app(&weak))
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap()
+        .run();
+
+    let handle = server.clone();
+    actix_rt::spawn(async move {
+        handle.stop(true).await;
+    });
+
+    reactor.serve(server, Duration::from_secs(5)).await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["task", "mailer", "pool"]);
+}
+
+#[actix_rt::test]
+async fn test_shutdown_of_shared_reactor() {
+    let log = Log::default();
+    let (reactor, _) = reactor(&log, false).await;
+    let _clone = reactor.clone();
+
+    let err = reactor
+        .shutdown(Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ShutdownError::Shared));
+    assert_eq!(*log.lock().unwrap(), vec!["task"]);
+}
+
+#[actix_rt::test]
+async fn test_shutdown_timeout() {
+    let log = Log::default();
+    let (reactor, _) = reactor(&log, true).await;
+
+    let err = reactor
+        .shutdown(Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ShutdownError::TimedOut { apps } if apps == ["test_shutdown::Mailer"]));
+    // the apps after the stuck one are finished anyway
+    assert_eq!(*log.lock().unwrap(), vec!["task", "pool"]);
+}
+
+#[actix_rt::test]
+async fn test_shutdown_waits_for_clones() {
+    let log = Log::default();
+    let (reactor, mut received) = reactor(&log, false).await;
+
+    // the clone is dropped while the reactor waits for it, once the tasks are stopped
+    let clone = reactor.clone();
+    actix_rt::spawn(async move {
+        assert_eq!(received.next().await, Some("stopped"));
+        drop(clone);
+    });
+
+    // well before the limit, which would fail with `ShutdownError::Shared`
+    reactor.shutdown(Duration::from_secs(60)).await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["task", "mailer", "pool"]);
+}
@@ -0,0 +1,148 @@
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use actix_rt::time::delay_for;
+use futures_channel::mpsc::{self, UnboundedSender};
+use futures_util::{FutureExt, StreamExt};
+use yeax::{App, BuildError, FrozenReactor, Reactor, Registry, Schedule};
+
+struct Sessions {
+    // handed to the tasks, which are the only ones holding it once the app is initialized
+    events: Mutex<Option<UnboundedSender<&'static str>>>,
+}
+
+impl Sessions {
+    fn new(events: UnboundedSender<&'static str>) -> Self {
+        Self {
+            events: Mutex::new(Some(events)),
+        }
+    }
+}
+
+impl App for Sessions {
+    fn init(&mut self, r: &mut Registry) {
+        let events = self.events.lock().unwrap().take().unwrap();
+        let polled = events.clone();
+        r.spawn(|_: FrozenReactor| async move {
+            let _ = polled.unbounded_send("started");
+            loop {
+                let _ = polled.unbounded_send("polled");
+                delay_for(Duration::from_millis(5)).await;
+            }
+        });
+        r.schedule(
+            Schedule::every(Duration::from_millis(10)),
+            move |_: FrozenReactor| {
+                let _ = events.unbounded_send("expired");
+                async {}
+            },
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_tasks() {
+    let (events, mut received) = mpsc::unbounded();
+    let reactor = Reactor::shared()
+        .add(Sessions::new(events))
+        .build()
+        .freeze();
+
+    // tasks only run once started
+    delay_for(Duration::from_millis(20)).await;
+    assert_eq!(received.next().now_or_never(), None);
+
+    reactor.start_tasks();
+    reactor.start_tasks();
+    let mut log = Vec::new();
+    let count = |log: &[&str], event| log.iter().filter(|&&logged| logged == event).count();
+    while count(&log, "polled") < 2 || count(&log, "expired") < 2 {
+        log.push(received.next().await.unwrap());
+    }
+    reactor.stop_tasks().await;
+
+    // stopped tasks are dropped along with their senders, and release the reactor
+    while let Some(event) = received.next().await {
+        log.push(event);
+    }
+    assert_eq!(count(&log, "started"), 1);
+    assert!(reactor.unfreeze().is_ok());
+}
+
+#[test]
+fn test_tasks_need_shared_reactor() {
+    // the default reactor can't be frozen to run the tasks
+    let err = Reactor::default()
+        .add(Sessions::new(mpsc::unbounded().0))
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        err.errors(),
+        [
+            BuildError::UnsharedTask {
+                app: "test_tasks::Sessions"
+            },
+            BuildError::UnsharedTask {
+                app: "test_tasks::Sessions"
+            },
+        ]
+    ));
+}
+
+fn at(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+#[test]
+fn test_cron_schedule() {
+    // saturday 2024-01-06 10:17:42 UTC
+    let now = at(1_704_536_262);
+
+    let every = Schedule::every(Duration::from_secs(60));
+    assert_eq!(every.next(now), Some(at(1_704_536_322)));
+
+    let cases = [
+        // at the next quarter
+        ("*/15 * * * *", 1_704_537_000),
+        // monday 2024-01-08 09:30
+        ("30 9 * * 1-5", 1_704_706_200),
+        // 2024-02-29
+        ("0 0 29 2 *", 1_709_164_800),
+        // either the 13th or a friday, friday 2024-01-12 12:00
+        ("0 12 13 * 5", 1_705_060_800),
+        // sunday is also 7
+        ("0 0 * * 7", 1_704_585_600),
+        // a start with a step goes to the end of the range, even with a step of 1
+        ("5/1 * * * *", 1_704_536_280),
+        ("20/30 * * * *", 1_704_536_400),
+    ];
+    for (expr, next) in cases.iter() {
+        let schedule = Schedule::cron(expr).unwrap();
+        assert_eq!(schedule.next(now), Some(at(*next)), "{}", expr);
+    }
+
+    // from 2024-03-01 the next 29th of february is in 2028
+    let leap_day = Schedule::cron("0 0 29 2 *").unwrap();
+    assert_eq!(leap_day.next(at(1_709_251_200)), Some(at(1_835_395_200)));
+
+    assert_eq!(Schedule::cron("0 0 31 2 *").unwrap().next(now), None);
+}
+
+#[test]
+fn test_invalid_cron() {
+    for expr in [
+        "* * * *",
+        "60 * * * *",
+        "* 24 * * *",
+        "* * 0 * *",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "a * * * *",
+    ]
+    .iter()
+    {
+        assert!(Schedule::cron(expr).is_err(), "{}", expr);
+    }
+}
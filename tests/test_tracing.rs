@@ -0,0 +1,172 @@
+#![cfg(feature = "tracing")]
+
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use actix_web::{web, HttpResponse};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use yeax::test::TestApp;
+use yeax::{App, MiddlewareConfig, Next, Reactor, Registry};
+
+/// Records the spans as their name followed by their fields, and the events as the span they
+/// happened in
+#[derive(Clone, Default)]
+struct Spans {
+    spans: Arc<Mutex<Vec<String>>>,
+    next_id: Arc<AtomicU64>,
+    entered: Arc<Mutex<Vec<u64>>>,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Spans {
+    fn named(&self, name: &str) -> Vec<String> {
+        let spans = self.spans.lock().unwrap();
+        spans
+            .iter()
+            .filter(|span| span.starts_with(name))
+            .cloned()
+            .collect()
+    }
+}
+
+struct Fields<'a>(&'a mut String);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = write!(self.0, " {}={:?}", field.name(), value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let _ = write!(self.0, " {}={}", field.name(), value);
+    }
+}
+
+impl Subscriber for Spans {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut span = attrs.metadata().name().to_string();
+        attrs.record(&mut Fields(&mut span));
+        self.spans.lock().unwrap().push(span);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, id: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        values.record(&mut Fields(&mut spans[id.into_u64() as usize - 1]));
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {
+        let span = match self.entered.lock().unwrap().last() {
+            Some(&id) => self.spans.lock().unwrap()[id as usize - 1].clone(),
+            None => String::new(),
+        };
+        self.events.lock().unwrap().push(span);
+    }
+
+    fn enter(&self, id: &Id) {
+        self.entered.lock().unwrap().push(id.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.entered.lock().unwrap().pop();
+    }
+}
+
+struct Auth;
+
+impl App for Auth {}
+
+struct Blog;
+
+impl App for Blog {
+    fn init(&mut self, r: &mut Registry) {
+        r.register_di(|_: &mut Blog, _: Option<&Auth>| {});
+    }
+
+    fn mount_point(&self) -> &'static str {
+        "/blog"
+    }
+
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("/posts", web::get().to(HttpResponse::Ok));
+    }
+
+    fn configure_middleware(&self, cfg: &mut MiddlewareConfig) {
+        // called before the request reaches the handler
+        cfg.wrap_scoped(|req, next: Next| {
+            tracing::info!("checked");
+            next.call(req)
+        });
+    }
+}
+
+struct Pages;
+
+impl App for Pages {
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route(
+            "/about",
+            web::get().to(|| {
+                tracing::info!("served");
+                HttpResponse::Ok()
+            }),
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_spans() {
+    let spans = Spans::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+
+    let reactor = Reactor::shared()
+        .add(Blog)
+        .add_named("staff", Auth)
+        .add(Pages);
+    let mut app = TestApp::new(reactor).await;
+    app.get("/blog/posts").await;
+    // served by the root app nested in the scope of `Auth`
+    app.get("/about").await;
+
+    assert_eq!(
+        spans.named("yeax::phase"),
+        vec![
+            "yeax::phase phase=pre_init app=test_tracing::Blog",
+            "yeax::phase phase=pre_init app=test_tracing::Auth instance=staff",
+            "yeax::phase phase=pre_init app=test_tracing::Pages",
+            "yeax::phase phase=init app=test_tracing::Blog",
+            "yeax::phase phase=init app=test_tracing::Auth instance=staff",
+            "yeax::phase phase=init app=test_tracing::Pages",
+            "yeax::phase phase=post_init app=test_tracing::Blog",
+            "yeax::phase phase=post_init app=test_tracing::Auth instance=staff",
+            "yeax::phase phase=post_init app=test_tracing::Pages",
+        ]
+    );
+    assert_eq!(
+        spans.named("yeax::inject"),
+        vec!["yeax::inject app=test_tracing::Blog params=(&mut test_tracing::Blog, Option<&test_tracing::Auth>)"]
+    );
+    assert_eq!(
+        spans.named("yeax::request"),
+        vec![
+            "yeax::request app=test_tracing::Blog method=GET path=/blog/posts status=200",
+            "yeax::request method=GET path=/about app=test_tracing::Pages status=200",
+        ]
+    );
+    // the events of the middleware and the handlers are attributed to the apps
+    assert_eq!(
+        *spans.events.lock().unwrap(),
+        vec![
+            "yeax::request app=test_tracing::Blog method=GET path=/blog/posts",
+            "yeax::request method=GET path=/about app=test_tracing::Pages",
+        ]
+    );
+}
@@ -0,0 +1,176 @@
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App as ActixApp, HttpResponse};
+use yeax::{ActixReactorExt, App, AppRef, Reactor};
+
+struct First;
+
+impl App for First {
+    fn mount_point(&self) -> &str {
+        "/first"
+    }
+
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("/name", web::get().to(|| HttpResponse::Ok().body("first")));
+    }
+}
+
+struct Second;
+
+impl App for Second {
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route("/name", web::get().to(|| HttpResponse::Ok().body("second")));
+    }
+}
+
+async fn get(reactor: &Reactor, path: &str) -> (StatusCode, String) {
+    let mut app = test::init_service(ActixApp::new().configure_app(reactor)).await;
+    let res = test::call_service(&mut app, test::TestRequest::get().uri(path).to_request()).await;
+    let status = res.status();
+    let body = test::read_body(res).await;
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[actix_rt::test]
+async fn test_default_mount_points() {
+    let reactor = Reactor::default().add(First).add(Second).build();
+
+    assert_eq!(get(&reactor, "/first/name").await.1, "first");
+    assert_eq!(get(&reactor, "/name").await.1, "second");
+}
+
+#[actix_rt::test]
+async fn test_overridden_mount_points() {
+    let reactor = Reactor::default()
+        .add_at("", First)
+        .add_at("/second", Second)
+        .build();
+
+    assert_eq!(get(&reactor, "/name").await.1, "first");
+    assert_eq!(get(&reactor, "/second/name").await.1, "second");
+    assert_eq!(get(&reactor, "/first/name").await.0, StatusCode::NOT_FOUND);
+}
+
+// the traces scope the root apps, which then catch the routes registered after them
+#[cfg(not(feature = "tracing"))]
+#[actix_rt::test]
+async fn test_routes_after_root_apps() {
+    let reactor = Reactor::default().add(First).add(Second).build();
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor).route(
+        "/project",
+        web::get().to(|| HttpResponse::Ok().body("project")),
+    ))
+    .await;
+
+    let req = test::TestRequest::get().uri("/project").to_request();
+    assert_eq!(test::read_response(&mut app, req).await, "project");
+    let req = test::TestRequest::get().uri("/name").to_request();
+    assert_eq!(test::read_response(&mut app, req).await, "second");
+}
+
+#[actix_rt::test]
+async fn test_frozen_mount_points() {
+    let reactor = Reactor::shared()
+        .add(First)
+        .add_at("/second", Second)
+        .build()
+        .freeze();
+
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor)).await;
+    for (path, name) in [("/first/name", "first"), ("/second/name", "second")].iter() {
+        let req = test::TestRequest::get().uri(path).to_request();
+        let body = test::read_response(&mut app, req).await;
+        assert_eq!(body, name.as_bytes());
+    }
+}
+
+struct Greeter {
+    greeting: &'static str,
+}
+
+impl App for Greeter {
+    fn configure_web(&self, cfg: &mut web::ServiceConfig) {
+        cfg.route(
+            "/greet",
+            web::get().to(|greeter: AppRef<Greeter>| HttpResponse::Ok().body(greeter.greeting)),
+        )
+        .route(
+            "/first",
+            web::get().to(|_: AppRef<First>| HttpResponse::Ok().finish()),
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_app_ref() {
+    let reactor = Reactor::shared()
+        .add(Greeter { greeting: "hello" })
+        .build()
+        .freeze();
+
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor)).await;
+    let req = test::TestRequest::get().uri("/greet").to_request();
+    assert_eq!(test::read_response(&mut app, req).await, "hello".as_bytes());
+}
+
+#[actix_rt::test]
+async fn test_app_ref_of_missing_app() {
+    let reactor = Reactor::shared()
+        .add(Greeter { greeting: "hello" })
+        .build()
+        .freeze();
+
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor)).await;
+    let req = test::TestRequest::get().uri("/first").to_request();
+    let res = test::call_service(&mut app, req).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[actix_rt::test]
+async fn test_app_ref_needs_frozen_reactor() {
+    let reactor = Reactor::default()
+        .add(Greeter { greeting: "hello" })
+        .build();
+
+    assert_eq!(
+        get(&reactor, "/greet").await.0,
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
+
+#[actix_rt::test]
+async fn test_named_mount_points() {
+    let reactor = Reactor::shared()
+        .add_named_at("staff", "/staff", Greeter { greeting: "staff" })
+        .add_named_at("customers", "/customers", Greeter { greeting: "hi" })
+        .add(Greeter { greeting: "hello" })
+        .build()
+        .freeze();
+
+    let staff = reactor.get_named::<Greeter>("staff").unwrap();
+    assert_eq!(staff.greeting, "staff");
+
+    // the handlers of each instance use it
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor)).await;
+    for (path, greeting) in [
+        ("/staff/greet", "staff"),
+        ("/customers/greet", "hi"),
+        ("/greet", "hello"),
+    ]
+    .iter()
+    {
+        let req = test::TestRequest::get().uri(path).to_request();
+        assert_eq!(
+            test::read_response(&mut app, req).await,
+            greeting.as_bytes()
+        );
+    }
+
+    // without the unnamed instance
+    let reactor = Reactor::shared()
+        .add_named_at("staff", "/staff", Greeter { greeting: "staff" })
+        .build()
+        .freeze();
+    let mut app = test::init_service(ActixApp::new().configure_app(&reactor)).await;
+    let req = test::TestRequest::get().uri("/staff/greet").to_request();
+    assert_eq!(test::read_response(&mut app, req).await, "staff".as_bytes());
+}
@@ -0,0 +1,20 @@
+use yeax::{App, Inject};
+
+struct Auth;
+
+impl App for Auth {}
+
+#[derive(Inject)]
+struct Bounded<'a: 'static> {
+    auth: &'a Auth,
+}
+
+#[derive(Inject)]
+struct Constrained<'a>
+where
+    Auth: 'a,
+{
+    auth: &'a Auth,
+}
+
+fn main() {}
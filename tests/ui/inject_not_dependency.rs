@@ -0,0 +1,13 @@
+use yeax::{App, Inject};
+
+struct Auth;
+
+impl App for Auth {}
+
+#[derive(Inject)]
+struct Deps<'a> {
+    auth: &'a Auth,
+    name: String,
+}
+
+fn main() {}
@@ -48,6 +48,11 @@ impl<T> JsonResponse<T> {
         }
     }
 
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
     pub fn next(mut self, next: String) -> Self {
         self.next = Some(next);
         self
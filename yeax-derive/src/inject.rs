@@ -0,0 +1,93 @@
+use proc_macro2::{Span, TokenStream};
+use proc_macro_crate::{crate_name, FoundCrate};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Data, DataStruct, GenericParam, Ident, Lifetime, Member};
+
+pub fn expand_derive(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
+    let fields = match &input.data {
+        Data::Struct(DataStruct { fields, .. }) => fields,
+        _ => return Err(vec![syn::Error::new_spanned(input, "Expected `struct`")]),
+    };
+
+    let mut errors = Vec::new();
+    let params = &input.generics.params;
+    let lifetime = match params.first() {
+        Some(GenericParam::Lifetime(param)) if params.len() == 1 => {
+            if let Some(bound) = param.bounds.first() {
+                errors.push(syn::Error::new_spanned(
+                    bound,
+                    "Expected a lifetime without bounds, the apps are borrowed for it",
+                ));
+            }
+            Some(&param.lifetime)
+        }
+        _ => {
+            errors.push(syn::Error::new_spanned(
+                input,
+                "Expected a single lifetime parameter, like `struct Deps<'a>`",
+            ));
+            None
+        }
+    };
+    if let Some(where_clause) = &input.generics.where_clause {
+        errors.push(syn::Error::new_spanned(
+            where_clause,
+            "Expected no `where` clause, the fields are fetched for any lifetime",
+        ));
+    }
+    let lifetime = match lifetime {
+        Some(lifetime) if errors.is_empty() => lifetime,
+        _ => return Err(errors),
+    };
+
+    let yeax = yeax_path();
+    let name = &input.ident;
+    // the lifetime of the loan the apps are fetched from
+    let loan = Lifetime::new("'__yeax_loan", Span::call_site());
+    // spanned by the fields, so the ones which aren't dependencies are reported in place
+    let field_params = fields.iter().map(|field| {
+        let ty = &field.ty;
+        quote_spanned!(ty.span()=> <#ty as #yeax::Dependency<#loan>>::params())
+    });
+    let fetches = fields.iter().enumerate().map(|(idx, field)| {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(idx.into()),
+        };
+        let ty = &field.ty;
+        quote_spanned!(ty.span()=> #member: <#ty as #yeax::Dependency<#loan>>::fetch(loan)?)
+    });
+
+    Ok(quote! {
+        impl<#loan, #lifetime> #yeax::Dependency<#loan> for #name<#lifetime> {
+            type Item = #name<#loan>;
+
+            fn params() -> ::std::vec::Vec<#yeax::Param> {
+                let mut params = ::std::vec::Vec::new();
+                #(params.extend(#field_params);)*
+                params
+            }
+
+            fn fetch(
+                loan: &mut #yeax::Loan<#loan>,
+            ) -> ::std::result::Result<Self::Item, #yeax::AppId> {
+                ::std::result::Result::Ok(#name {
+                    #(#fetches,)*
+                })
+            }
+        }
+    })
+}
+
+/// The path of `yeax` as the deriving crate depends on it, which may have renamed it
+fn yeax_path() -> TokenStream {
+    match crate_name("yeax") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = Ident::new(&name, Span::call_site());
+            quote!(::#name)
+        }
+        // the tests of yeax itself, which see it as an external crate
+        Ok(FoundCrate::Itself) | Err(_) => quote!(::yeax),
+    }
+}
@@ -0,0 +1,37 @@
+extern crate proc_macro;
+use proc_macro::TokenStream;
+mod inject;
+use quote::quote;
+
+/// Lets a struct of apps be the parameter of a DI hook, its fields are fetched like the parameters
+/// of the hook, so a hook can ask for any number of apps by name:
+///
+/// ```ignore
+/// #[derive(Inject)]
+/// struct Deps<'a> {
+///     blog: &'a mut BlogApp,
+///     auth: &'a AuthApp,
+///     mail: Option<&'a MailApp>,
+///     staff: Named<Staff, &'a AuthApp>,
+/// }
+///
+/// r.register_di(|deps: Deps| {
+///     // ...
+/// });
+/// ```
+///
+/// The struct should have a single lifetime parameter without bounds or `where` clause, the one
+/// the apps are borrowed for, and every field should be a parameter a hook could take.
+#[proc_macro_derive(Inject)]
+pub fn derive_inject(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    inject::expand_derive(&input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
+
+fn to_compile_errors(errors: Vec<syn::Error>) -> proc_macro2::TokenStream {
+    let compile_errors = errors.iter().map(syn::Error::to_compile_error);
+    quote!(#(#compile_errors)*)
+}